@@ -1,6 +1,6 @@
 //! Adds ability to serialize and deserialize types from the [HTTP][http] crate.
 //!
-//! If you want to serialize `Request` or `Response`, use `into_parts()` and serialize their parts, and then rebuild them using their `Builder`.
+//! If you want to serialize `Request` or `Response`, use `into_parts()` and serialize their parts with `http_serde::request_parts` or `http_serde::response_parts`, and then rebuild them using `from_parts()`.
 //!
 //! [serde]: https://lib.rs/serde
 //! [http]: https://lib.rs/http
@@ -32,21 +32,50 @@
 //!     authority: Authority,
 //! }
 //! ```
+//!
+//! ## `no_std`
+//!
+//! The crate itself only needs `alloc`. Disable default features and enable `alloc` to build it without the `std` feature
+//! (`cookie_jar`, `header_map_mime` and `uri_as_url` aren't available then, even with the `cookie`, `mime` or `url` features
+//! enabled, and the `retry_after` feature turns `std` back on). Note that the `http` crate still depends on `std`.
+
+#![no_std]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("http-serde needs the `alloc` or `std` feature");
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+pub mod option;
+pub mod wrap;
+
+#[cfg(feature = "serde_with")]
+mod serde_as;
+#[cfg(feature = "serde_with")]
+pub use serde_as::As;
 
 /// For `http::HeaderMap`
 ///
 /// `#[serde(with = "http_serde::header_map")]`
 pub mod header_map {
+    use alloc::borrow::Cow;
+    use alloc::vec::Vec;
+    use core::fmt;
     use http::header::{GetAll, HeaderName};
     use http::{HeaderMap, HeaderValue};
     use serde::de;
     use serde::de::{Deserializer, MapAccess, Unexpected, Visitor};
     use serde::ser::SerializeSeq;
     use serde::{Serialize, Serializer};
-    use std::borrow::Cow;
-    use std::fmt;
 
-    struct ToSeq<'a>(GetAll<'a, HeaderValue>);
+    pub(crate) struct ToSeq<'a>(pub(crate) GetAll<'a, HeaderValue>);
 
     impl<'a> Serialize for ToSeq<'a> {
         fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
@@ -59,7 +88,12 @@ pub mod header_map {
                         }
                     }
                 }
-                ser.collect_seq(self.0.iter().filter_map(|v| v.to_str().ok()))
+                // Strings and byte arrays can't be mixed, so if any value isn't UTF-8 all are written as bytes
+                if self.0.iter().all(|v| v.to_str().is_ok()) {
+                    ser.collect_seq(self.0.iter().filter_map(|v| v.to_str().ok()))
+                } else {
+                    ser.collect_seq(self.0.iter().map(|v| v.as_bytes()))
+                }
             } else {
                 let mut seq = ser.serialize_seq(Some(count))?;
                 for v in self.0.iter() {
@@ -87,8 +121,31 @@ pub mod header_map {
         Bytes(Vec<Cow<'a, [u8]>>),
     }
 
-    struct HeaderMapVisitor {
-        is_human_readable: bool,
+    pub(crate) struct HeaderMapVisitor {
+        pub(crate) is_human_readable: bool,
+        /// `None` keeps the original behavior of a single value replacing earlier ones
+        pub(crate) duplicates: Option<crate::DupStrategy>,
+    }
+
+    impl HeaderMapVisitor {
+        /// Whether values for `key` should be added, after making room for them if needed
+        fn admit<E: de::Error>(&self, map: &mut HeaderMap, key: &HeaderName) -> Result<bool, E> {
+            if !map.contains_key(key) {
+                return Ok(true);
+            }
+            match self.duplicates {
+                None | Some(crate::DupStrategy::Append) => Ok(true),
+                Some(crate::DupStrategy::FirstWins) => Ok(false),
+                Some(crate::DupStrategy::LastWins) => {
+                    map.remove(key);
+                    Ok(true)
+                }
+                Some(crate::DupStrategy::Error) => Err(de::Error::custom(format_args!(
+                    "duplicate header `{}`",
+                    key
+                ))),
+            }
+        }
     }
 
     impl<'de> Visitor<'de> for HeaderMapVisitor {
@@ -109,6 +166,9 @@ pub mod header_map {
                 while let Some((key, arr)) = access.next_entry::<Cow<str>, Vec<Cow<[u8]>>>()? {
                     let key = HeaderName::from_bytes(key.as_bytes())
                         .map_err(|_| de::Error::invalid_value(Unexpected::Str(&key), &self))?;
+                    if !self.admit(&mut map, &key)? {
+                        continue;
+                    }
                     for val in arr {
                         let val = HeaderValue::from_bytes(&val).map_err(|_| {
                             de::Error::invalid_value(Unexpected::Bytes(&val), &self)
@@ -120,12 +180,19 @@ pub mod header_map {
                 while let Some((key, val)) = access.next_entry::<Cow<str>, OneOrMore>()? {
                     let key = HeaderName::from_bytes(key.as_bytes())
                         .map_err(|_| de::Error::invalid_value(Unexpected::Str(&key), &self))?;
+                    if !self.admit(&mut map, &key)? {
+                        continue;
+                    }
                     match val {
                         OneOrMore::One(val) => {
                             let val = val.parse().map_err(|_| {
                                 de::Error::invalid_value(Unexpected::Str(&val), &self)
                             })?;
-                            map.insert(key, val);
+                            if self.duplicates.is_some() {
+                                map.append(key, val);
+                            } else {
+                                map.insert(key, val);
+                            }
                         }
                         OneOrMore::Strings(arr) => {
                             for val in arr {
@@ -156,7 +223,258 @@ pub mod header_map {
         D: Deserializer<'de>,
     {
         let is_human_readable = de.is_human_readable();
-        de.deserialize_map(HeaderMapVisitor { is_human_readable })
+        de.deserialize_map(HeaderMapVisitor {
+            is_human_readable,
+            duplicates: None,
+        })
+    }
+
+    /// For `http::HeaderMap`, with values that aren't UTF-8 encoded as Base64 in human-readable formats
+    ///
+    /// `#[serde(with = "http_serde::header_map::base64")]`
+    ///
+    /// Like `http_serde::header_map`, but a value that isn't valid UTF-8 is written as `{"base64": "..."}`
+    /// (standard alphabet, with padding) instead of an array of bytes, so `{"a": ["ok", {"base64": "/w=="}]}`.
+    /// Both plain strings and the Base64 objects are accepted when deserializing. Binary formats aren't affected.
+    pub mod base64 {
+        use alloc::string::String;
+        use alloc::vec;
+        use alloc::vec::Vec;
+        use core::fmt;
+        use http::header::{GetAll, HeaderName};
+        use http::{HeaderMap, HeaderValue};
+        use serde::de::{MapAccess, Unexpected, Visitor};
+        use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        fn encode(bytes: &[u8]) -> String {
+            let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+            for chunk in bytes.chunks(3) {
+                let n = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+                for i in 0..4 {
+                    if i <= chunk.len() {
+                        out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+                    } else {
+                        out.push('=');
+                    }
+                }
+            }
+            out
+        }
+
+        fn decode(text: &str) -> Option<Vec<u8>> {
+            let text = text.trim_end_matches('=');
+            let mut out = Vec::with_capacity(text.len() * 3 / 4);
+            let mut n = 0u32;
+            for (i, c) in text.bytes().enumerate() {
+                let digit = ALPHABET.iter().position(|&a| a == c)? as u32;
+                n = n << 6 | digit;
+                if i % 4 == 3 {
+                    out.extend_from_slice(&n.to_be_bytes()[1..]);
+                    n = 0;
+                }
+            }
+            match text.len() % 4 {
+                0 => {}
+                2 => out.push((n >> 4) as u8),
+                3 => out.extend_from_slice(&((n >> 2) as u16).to_be_bytes()),
+                _ => return None,
+            }
+            Some(out)
+        }
+
+        struct Value<'a>(&'a HeaderValue);
+
+        impl Serialize for Value<'_> {
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                match self.0.to_str() {
+                    Ok(s) => ser.serialize_str(s),
+                    Err(_) => {
+                        ser.collect_map(core::iter::once(("base64", encode(self.0.as_bytes()))))
+                    }
+                }
+            }
+        }
+
+        struct Values<'a>(GetAll<'a, HeaderValue>);
+
+        impl Serialize for Values<'_> {
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                let mut values = self.0.iter();
+                match (values.next(), values.next()) {
+                    (Some(one), None) => Value(one).serialize(ser),
+                    _ => ser.collect_seq(self.0.iter().map(Value)),
+                }
+            }
+        }
+
+        /// Implementation detail. Use derive annotations instead.
+        pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+            if !ser.is_human_readable() {
+                return super::serialize(headers, ser);
+            }
+            ser.collect_map(
+                headers
+                    .keys()
+                    .map(|k| (k.as_str(), Values(headers.get_all(k)))),
+            )
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Encoded {
+            Str(String),
+            Base64 { base64: String },
+        }
+
+        impl Encoded {
+            fn into_value<E: de::Error>(self) -> Result<HeaderValue, E> {
+                match self {
+                    Encoded::Str(s) => HeaderValue::from_str(&s).map_err(|_| {
+                        de::Error::invalid_value(Unexpected::Str(&s), &"header value")
+                    }),
+                    Encoded::Base64 { base64 } => {
+                        let bytes = decode(&base64).ok_or_else(|| {
+                            de::Error::invalid_value(Unexpected::Str(&base64), &"Base64")
+                        })?;
+                        HeaderValue::from_bytes(&bytes).map_err(|_| {
+                            de::Error::invalid_value(Unexpected::Bytes(&bytes), &"header value")
+                        })
+                    }
+                }
+            }
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMore {
+            One(Encoded),
+            More(Vec<Encoded>),
+        }
+
+        struct Base64Visitor;
+
+        impl<'de> Visitor<'de> for Base64Visitor {
+            type Value = HeaderMap;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("map of header names to strings or Base64 objects")
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, mut access: M) -> Result<HeaderMap, M::Error> {
+                let mut map = HeaderMap::with_capacity(access.size_hint().unwrap_or(0));
+                while let Some((key, val)) = access.next_entry::<String, OneOrMore>()? {
+                    let key = HeaderName::from_bytes(key.as_bytes())
+                        .map_err(|_| de::Error::invalid_value(Unexpected::Str(&key), &self))?;
+                    let values = match val {
+                        OneOrMore::One(one) => vec![one],
+                        OneOrMore::More(more) => more,
+                    };
+                    for val in values {
+                        map.append(&key, val.into_value()?);
+                    }
+                }
+                Ok(map)
+            }
+        }
+
+        /// Implementation detail.
+        pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            if !de.is_human_readable() {
+                return super::deserialize(de);
+            }
+            de.deserialize_map(Base64Visitor)
+        }
+    }
+
+    /// For `http::HeaderMap`, with names in alphabetical order
+    ///
+    /// `#[serde(with = "http_serde::header_map::sorted")]`
+    ///
+    /// The same as `http_serde::header_map`, except that names are sorted, so equal maps always serialize the same,
+    /// no matter in which order the headers were inserted. Values of each name stay in the order they were appended.
+    pub mod sorted {
+        use super::ToSeq;
+        use alloc::vec::Vec;
+        use http::HeaderMap;
+        use serde::{Deserializer, Serializer};
+
+        /// Implementation detail. Use derive annotations instead.
+        pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+            let mut names: Vec<_> = headers.keys().collect();
+            names.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+            ser.collect_map(
+                names
+                    .into_iter()
+                    .map(|k| (k.as_str(), ToSeq(headers.get_all(k)))),
+            )
+        }
+
+        /// Implementation detail.
+        pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::deserialize(de)
+        }
+    }
+
+    /// For `http::HeaderMap`, as a list of `[name, value]` pairs
+    ///
+    /// `#[serde(with = "http_serde::header_map::ordered")]`
+    ///
+    /// Serialized as `[["a", "1"], ["b", "2"], ["a", "3"]]`, in the order of `HeaderMap::iter()`, and deserialized by
+    /// appending the pairs in the same order. A pair's position in the list is kept even if it's out of order,
+    /// but note that `HeaderMap` itself groups values by name: names are in the order they were first inserted,
+    /// and all values of a name come together. Values use the same format as `http_serde::header_value`.
+    pub mod ordered {
+        use alloc::vec::Vec;
+        use http::header::HeaderName;
+        use http::{HeaderMap, HeaderValue};
+        use serde::ser::SerializeSeq;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        #[derive(Serialize)]
+        struct PairSer<'a>(
+            #[serde(with = "crate::header_name")] &'a HeaderName,
+            #[serde(with = "crate::header_value")] &'a HeaderValue,
+        );
+
+        #[derive(Deserialize)]
+        struct PairDe(
+            #[serde(with = "crate::header_name")] HeaderName,
+            #[serde(with = "crate::header_value")] HeaderValue,
+        );
+
+        /// Implementation detail. Use derive annotations instead.
+        pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+            let mut seq = ser.serialize_seq(Some(headers.len()))?;
+            for (name, value) in headers {
+                seq.serialize_element(&PairSer(name, value))?;
+            }
+            seq.end()
+        }
+
+        /// Implementation detail.
+        pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let pairs = Vec::<PairDe>::deserialize(de)?;
+            let mut headers = HeaderMap::with_capacity(pairs.len());
+            for PairDe(name, value) in pairs {
+                headers.append(name, value);
+            }
+            Ok(headers)
+        }
     }
 }
 
@@ -164,11 +482,11 @@ pub mod header_map {
 ///
 /// `#[serde(with = "http_serde::status_code")]`
 pub mod status_code {
+    use core::fmt;
     use http::StatusCode;
     use serde::de;
     use serde::de::{Unexpected, Visitor};
     use serde::{Deserializer, Serializer};
-    use std::fmt;
 
     /// Implementation detail. Use derive annotations instead.
     pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
@@ -212,14 +530,88 @@ pub mod status_code {
             StatusCode::from_u16(val)
                 .map_err(|_| de::Error::invalid_value(Unexpected::Unsigned(val.into()), &self))
         }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            let code = val
+                .parse()
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))?;
+            self.visit_u16(code)
+        }
+
+        fn visit_borrowed_str<E: de::Error>(self, val: &'de str) -> Result<Self::Value, E> {
+            self.visit_str(val)
+        }
     }
 
     /// Implementation detail.
+    ///
+    /// In human-readable formats the code can also be a string, like `"404"`.
     pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
     where
         D: Deserializer<'de>,
     {
-        de.deserialize_u16(StatusVisitor)
+        if de.is_human_readable() {
+            de.deserialize_any(StatusVisitor)
+        } else {
+            de.deserialize_u16(StatusVisitor)
+        }
+    }
+
+    /// For `http::StatusCode`, with its reason phrase in human-readable formats
+    ///
+    /// `#[serde(with = "http_serde::status_code::with_reason")]`
+    ///
+    /// Serialized as `{"code": 404, "reason": "Not Found"}`, with the reason from `StatusCode::canonical_reason()`,
+    /// or `null` for codes that don't have one. Binary formats use a plain number, like `http_serde::status_code`.
+    /// Both the object and a plain number are accepted when deserializing. The reason is ignored.
+    pub mod with_reason {
+        use alloc::string::String;
+        use http::StatusCode;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        #[derive(Serialize)]
+        #[serde(rename = "StatusCode")]
+        struct WithReason {
+            code: u16,
+            reason: Option<&'static str>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Object {
+                #[serde(with = "super")]
+                code: StatusCode,
+                #[serde(default, rename = "reason")]
+                _reason: Option<String>,
+            },
+            Bare(#[serde(with = "super")] StatusCode),
+        }
+
+        /// Implementation detail. Use derive annotations instead.
+        pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+            if !ser.is_human_readable() {
+                return super::serialize(status, ser);
+            }
+            WithReason {
+                code: status.as_u16(),
+                reason: status.canonical_reason(),
+            }
+            .serialize(ser)
+        }
+
+        /// Implementation detail.
+        pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            if !de.is_human_readable() {
+                return super::deserialize(de);
+            }
+            match Repr::deserialize(de)? {
+                Repr::Object { code, .. } | Repr::Bare(code) => Ok(code),
+            }
+        }
     }
 }
 
@@ -227,17 +619,29 @@ pub mod status_code {
 ///
 /// `#[serde(with = "http_serde::method")]`
 pub mod method {
+    use core::fmt;
     use http::Method;
     use serde::de;
     use serde::de::{Unexpected, Visitor};
     use serde::{Deserializer, Serializer};
-    use std::fmt;
 
     /// Implementation detail. Use derive annotations instead.
     pub fn serialize<S: Serializer>(method: &Method, ser: S) -> Result<S::Ok, S::Error> {
         ser.serialize_str(method.as_str())
     }
 
+    const STANDARD: [Method; 9] = [
+        Method::GET,
+        Method::HEAD,
+        Method::POST,
+        Method::PUT,
+        Method::DELETE,
+        Method::CONNECT,
+        Method::OPTIONS,
+        Method::TRACE,
+        Method::PATCH,
+    ];
+
     struct MethodVisitor;
 
     impl<'de> Visitor<'de> for MethodVisitor {
@@ -248,12 +652,20 @@ pub mod method {
         }
 
         fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            if let Some(method) = STANDARD
+                .iter()
+                .find(|m| m.as_str().eq_ignore_ascii_case(val))
+            {
+                return Ok(method.clone());
+            }
             val.parse()
                 .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))
         }
     }
 
     /// Implementation detail.
+    ///
+    /// The standard methods are case-insensitive (`"get"` is `GET`). Extension methods are kept as they are.
     pub fn deserialize<'de, D>(de: D) -> Result<Method, D::Error>
     where
         D: Deserializer<'de>,
@@ -266,12 +678,13 @@ pub mod method {
 ///
 /// `#[serde(with = "http_serde::uri")]`
 pub mod uri {
+    use alloc::string::String;
+    use core::convert::TryInto;
+    use core::fmt;
     use http::Uri;
     use serde::de;
     use serde::de::{Unexpected, Visitor};
     use serde::{Deserializer, Serializer};
-    use std::convert::TryInto;
-    use std::fmt;
 
     /// Implementation detail. Use derive annotations instead.
     pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
@@ -310,12 +723,13 @@ pub mod uri {
 ///
 /// `#[serde(with = "http_serde::authority")]`
 pub mod authority {
+    use alloc::string::String;
+    use core::convert::TryInto;
+    use core::fmt;
     use http::uri::Authority;
     use serde::de;
     use serde::de::{Unexpected, Visitor};
     use serde::{Deserializer, Serializer};
-    use std::convert::TryInto;
-    use std::fmt;
 
     /// Implementation detail. Use derive annotations instead.
     pub fn serialize<S: Serializer>(authority: &Authority, ser: S) -> Result<S::Ok, S::Error> {
@@ -350,17 +764,56 @@ pub mod authority {
     }
 }
 
-/// For `http::uri::Authority`
+/// For `http::Version`
 ///
-/// `#[serde(with = "http_serde::authority")]`
+/// `#[serde(with = "http_serde::version")]`
+///
+/// Serialized as a string like `"HTTP/1.1"` or `"HTTP/2.0"` in human-readable formats. Binary formats use a single
+/// byte: 0 for HTTP/0.9, 1 for HTTP/1.0, 2 for HTTP/1.1, 3 for HTTP/2.0 and 4 for HTTP/3.0.
+///
+/// Should a future version of the `http` crate add a version not known here, it's serialized using its `Debug`
+/// representation (or 255 in binary formats) instead of failing. Such versions don't round-trip: deserializing
+/// that output is an error, because there's no `Version` it could be read back as.
 pub mod version {
+    use alloc::string::String;
+    use core::convert::TryFrom;
+    use core::fmt::{Debug, Formatter};
     use http::Version;
     use serde::de::{Unexpected, Visitor};
     use serde::{de, Deserializer, Serializer};
-    use std::fmt::Formatter;
 
+    const VERSIONS: [(Version, &str); 5] = [
+        (Version::HTTP_09, "HTTP/0.9"),
+        (Version::HTTP_10, "HTTP/1.0"),
+        (Version::HTTP_11, "HTTP/1.1"),
+        (Version::HTTP_2, "HTTP/2.0"),
+        (Version::HTTP_3, "HTTP/3.0"),
+    ];
+
+    /// Binary representation of versions not in `VERSIONS`
+    const UNKNOWN: u8 = 255;
+
+    /// Implementation detail. Use derive annotations instead.
     pub fn serialize<S: Serializer>(version: &Version, ser: S) -> Result<S::Ok, S::Error> {
-        ser.serialize_str(format!("{:?}", version).as_str())
+        match VERSIONS.iter().position(|(v, _)| v == version) {
+            Some(i) if ser.is_human_readable() => ser.serialize_str(VERSIONS[i].1),
+            Some(i) => ser.serialize_u8(i as u8),
+            None => serialize_unknown(version, ser),
+        }
+    }
+
+    /// Implementation detail. The fallback for versions not known to this crate, which only needs their `Debug` representation.
+    #[doc(hidden)]
+    pub fn serialize_unknown<T, S>(version: &T, ser: S) -> Result<S::Ok, S::Error>
+    where
+        T: Debug + ?Sized,
+        S: Serializer,
+    {
+        if ser.is_human_readable() {
+            ser.collect_str(&format_args!("{:?}", version))
+        } else {
+            ser.serialize_u8(UNKNOWN)
+        }
     }
 
     struct VersionVisitor;
@@ -368,30 +821,3738 @@ pub mod version {
     impl<'de> Visitor<'de> for VersionVisitor {
         type Value = Version;
 
-        fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
             write!(formatter, "valid version")
         }
 
         fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
-            Ok(match val {
-                "HTTP/0.9" => Version::HTTP_09,
-                "HTTP/1.0" => Version::HTTP_10,
-                "HTTP/1.1" => Version::HTTP_11,
-                "HTTP/2.0" => Version::HTTP_2,
-                "HTTP/3.0" => Version::HTTP_3,
-                _ => Err(de::Error::invalid_value(Unexpected::Str(val), &self))?,
-            })
+            VERSIONS
+                .iter()
+                .find(|(_, name)| *name == val)
+                .map(|(v, _)| *v)
+                .ok_or_else(|| de::Error::invalid_value(Unexpected::Str(val), &self))
         }
 
         fn visit_string<E: de::Error>(self, val: String) -> Result<Self::Value, E> {
             self.visit_str(val.as_str())
         }
+
+        fn visit_u8<E: de::Error>(self, val: u8) -> Result<Self::Value, E> {
+            VERSIONS
+                .get(usize::from(val))
+                .map(|(v, _)| *v)
+                .ok_or_else(|| de::Error::invalid_value(Unexpected::Unsigned(val.into()), &self))
+        }
+
+        fn visit_u64<E: de::Error>(self, val: u64) -> Result<Self::Value, E> {
+            match u8::try_from(val) {
+                Ok(val) => self.visit_u8(val),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Unsigned(val), &self)),
+            }
+        }
     }
 
+    /// Implementation detail.
     pub fn deserialize<'de, D>(de: D) -> Result<Version, D::Error>
     where
         D: Deserializer<'de>,
     {
-        de.deserialize_str(VersionVisitor)
+        if de.is_human_readable() {
+            de.deserialize_str(VersionVisitor)
+        } else {
+            de.deserialize_u8(VersionVisitor)
+        }
+    }
+}
+
+/// For `http::header::HeaderName`
+///
+/// `#[serde(with = "http_serde::header_name")]`
+///
+/// Serialized as the lowercase name.
+pub mod header_name {
+    use core::fmt;
+    use http::header::HeaderName;
+    use serde::de;
+    use serde::de::{Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(name: &HeaderName, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(name.as_str())
+    }
+
+    /// Implementation detail.
+    pub struct HeaderNameVisitor;
+
+    impl<'de> Visitor<'de> for HeaderNameVisitor {
+        type Value = HeaderName;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "valid header name")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            HeaderName::from_bytes(val.as_bytes())
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))
+        }
+
+        fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+            HeaderName::from_bytes(val)
+                .map_err(|_| de::Error::invalid_value(Unexpected::Bytes(val), &self))
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderName, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        de.deserialize_str(HeaderNameVisitor)
+    }
+}
+
+/// For `http::HeaderValue`
+///
+/// `#[serde(with = "http_serde::header_value")]`
+///
+/// In human-readable formats it's serialized as a string if it's valid UTF-8, and as bytes otherwise.
+/// Binary formats always use bytes, so that any value round-trips.
+pub mod header_value {
+    use alloc::vec::Vec;
+    use core::fmt;
+    use http::HeaderValue;
+    use serde::de;
+    use serde::de::{SeqAccess, Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(value: &HeaderValue, ser: S) -> Result<S::Ok, S::Error> {
+        match value.to_str() {
+            Ok(s) if ser.is_human_readable() => ser.serialize_str(s),
+            _ => ser.serialize_bytes(value.as_bytes()),
+        }
+    }
+
+    /// Implementation detail.
+    pub struct HeaderValueVisitor;
+
+    impl<'de> Visitor<'de> for HeaderValueVisitor {
+        type Value = HeaderValue;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "valid header value")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            HeaderValue::from_str(val)
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))
+        }
+
+        fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+            HeaderValue::from_bytes(val)
+                .map_err(|_| de::Error::invalid_value(Unexpected::Bytes(val), &self))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(b) = seq.next_element()? {
+                bytes.push(b);
+            }
+            self.visit_bytes(&bytes)
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderValue, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if de.is_human_readable() {
+            de.deserialize_any(HeaderValueVisitor)
+        } else {
+            de.deserialize_byte_buf(HeaderValueVisitor)
+        }
+    }
+}
+
+/// For `http::uri::Scheme`
+///
+/// `#[serde(with = "http_serde::scheme")]`
+pub mod scheme {
+    use core::fmt;
+    use http::uri::Scheme;
+    use serde::de;
+    use serde::de::{Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(scheme: &Scheme, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(&scheme)
+    }
+
+    struct SchemeVisitor;
+
+    impl<'de> Visitor<'de> for SchemeVisitor {
+        type Value = Scheme;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "valid scheme")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            if val.is_empty() {
+                return Err(de::Error::invalid_value(Unexpected::Str(val), &self));
+            }
+            val.parse()
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Scheme, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        de.deserialize_str(SchemeVisitor)
+    }
+}
+
+/// For `http::uri::PathAndQuery`
+///
+/// `#[serde(with = "http_serde::path_and_query")]`
+///
+/// Accepts paths starting with `/`, and `*` (the target of `OPTIONS * HTTP/1.1`).
+/// An empty string is deserialized as `/`, which is also how `http` displays an empty path.
+pub mod path_and_query {
+    use core::fmt;
+    use http::uri::PathAndQuery;
+    use serde::de;
+    use serde::de::{Unexpected, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(pq: &PathAndQuery, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(&pq)
+    }
+
+    struct PathAndQueryVisitor;
+
+    impl<'de> Visitor<'de> for PathAndQueryVisitor {
+        type Value = PathAndQuery;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "path starting with / or *")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            if val.is_empty() {
+                return Ok(PathAndQuery::from_static("/"));
+            }
+            if val != "*" && !val.starts_with('/') {
+                return Err(de::Error::invalid_value(Unexpected::Str(val), &self));
+            }
+            val.parse()
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<PathAndQuery, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        de.deserialize_str(PathAndQueryVisitor)
+    }
+}
+
+mod common_status {
+    use core::convert::TryFrom;
+    use core::hash::{Hash, Hasher};
+    use http::status::InvalidStatusCode;
+    use http::StatusCode;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// A `StatusCode` as an enum of commonly used codes, for exhaustive matching
+    ///
+    /// Implements `Serialize` and `Deserialize` directly, as a number like `http_serde::status_code`.
+    /// Codes without a dedicated variant are kept in `Other`. Deserialization and `From<StatusCode>` always pick
+    /// the dedicated variant when there is one, so `Other(200)` is never produced. You can still construct it yourself,
+    /// so equality and hashing compare the numeric code: `Other(200) == Ok`.
+    ///
+    /// Converting back to `StatusCode` is `TryFrom` rather than `From`, because `Other` can hold any `u16`,
+    /// including numbers that aren't valid status codes (outside 100-999).
+    #[derive(Debug, Clone, Copy)]
+    pub enum CommonStatus {
+        /// 200
+        Ok,
+        /// 201
+        Created,
+        /// 202
+        Accepted,
+        /// 204
+        NoContent,
+        /// 301
+        MovedPermanently,
+        /// 302
+        Found,
+        /// 304
+        NotModified,
+        /// 400
+        BadRequest,
+        /// 401
+        Unauthorized,
+        /// 403
+        Forbidden,
+        /// 404
+        NotFound,
+        /// 405
+        MethodNotAllowed,
+        /// 409
+        Conflict,
+        /// 422
+        UnprocessableEntity,
+        /// 429
+        TooManyRequests,
+        /// 500
+        InternalServerError,
+        /// 502
+        BadGateway,
+        /// 503
+        ServiceUnavailable,
+        /// 504
+        GatewayTimeout,
+        /// Any other code
+        Other(u16),
+    }
+
+    impl CommonStatus {
+        /// Numeric value of the status code
+        pub fn as_u16(&self) -> u16 {
+            match *self {
+                CommonStatus::Ok => 200,
+                CommonStatus::Created => 201,
+                CommonStatus::Accepted => 202,
+                CommonStatus::NoContent => 204,
+                CommonStatus::MovedPermanently => 301,
+                CommonStatus::Found => 302,
+                CommonStatus::NotModified => 304,
+                CommonStatus::BadRequest => 400,
+                CommonStatus::Unauthorized => 401,
+                CommonStatus::Forbidden => 403,
+                CommonStatus::NotFound => 404,
+                CommonStatus::MethodNotAllowed => 405,
+                CommonStatus::Conflict => 409,
+                CommonStatus::UnprocessableEntity => 422,
+                CommonStatus::TooManyRequests => 429,
+                CommonStatus::InternalServerError => 500,
+                CommonStatus::BadGateway => 502,
+                CommonStatus::ServiceUnavailable => 503,
+                CommonStatus::GatewayTimeout => 504,
+                CommonStatus::Other(code) => code,
+            }
+        }
+    }
+
+    impl PartialEq for CommonStatus {
+        fn eq(&self, other: &Self) -> bool {
+            self.as_u16() == other.as_u16()
+        }
+    }
+
+    impl Eq for CommonStatus {}
+
+    impl Hash for CommonStatus {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.as_u16().hash(state);
+        }
+    }
+
+    impl From<StatusCode> for CommonStatus {
+        fn from(status: StatusCode) -> Self {
+            match status.as_u16() {
+                200 => CommonStatus::Ok,
+                201 => CommonStatus::Created,
+                202 => CommonStatus::Accepted,
+                204 => CommonStatus::NoContent,
+                301 => CommonStatus::MovedPermanently,
+                302 => CommonStatus::Found,
+                304 => CommonStatus::NotModified,
+                400 => CommonStatus::BadRequest,
+                401 => CommonStatus::Unauthorized,
+                403 => CommonStatus::Forbidden,
+                404 => CommonStatus::NotFound,
+                405 => CommonStatus::MethodNotAllowed,
+                409 => CommonStatus::Conflict,
+                422 => CommonStatus::UnprocessableEntity,
+                429 => CommonStatus::TooManyRequests,
+                500 => CommonStatus::InternalServerError,
+                502 => CommonStatus::BadGateway,
+                503 => CommonStatus::ServiceUnavailable,
+                504 => CommonStatus::GatewayTimeout,
+                code => CommonStatus::Other(code),
+            }
+        }
+    }
+
+    /// Fails only if `Other` holds a number that isn't a valid status code
+    impl TryFrom<CommonStatus> for StatusCode {
+        type Error = InvalidStatusCode;
+
+        fn try_from(status: CommonStatus) -> Result<Self, Self::Error> {
+            StatusCode::from_u16(status.as_u16())
+        }
+    }
+
+    impl Serialize for CommonStatus {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            ser.serialize_u16(self.as_u16())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for CommonStatus {
+        fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            crate::status_code::deserialize(de).map(CommonStatus::from)
+        }
+    }
+}
+
+pub use common_status::CommonStatus;
+
+/// For `http::HeaderMap`, without hop-by-hop headers
+///
+/// `#[serde(with = "http_serde::header_map_end_to_end")]`
+///
+/// Headers that are only meaningful for a single connection are left out, so the map is safe to cache or store.
+/// These are the headers listed in [`HOP_BY_HOP`](header_map_end_to_end::HOP_BY_HOP)
+/// (`Connection`, `Keep-Alive`, `Proxy-Authenticate`, `Proxy-Authorization`, `TE`, `Trailer`, `Transfer-Encoding`, `Upgrade`),
+/// and any header named in the `Connection` header's value.
+///
+/// They're removed both when serializing and deserializing.
+pub mod header_map_end_to_end {
+    use crate::header_map::ToSeq;
+    use alloc::vec::Vec;
+    use http::header::{HeaderName, CONNECTION};
+    use http::HeaderMap;
+    use serde::{Deserializer, Serializer};
+
+    /// Hop-by-hop headers defined by RFC 7230 section 6.1 and RFC 2616 section 13.5.1
+    pub const HOP_BY_HOP: &[&str] = &[
+        "connection",
+        "keep-alive",
+        "proxy-authenticate",
+        "proxy-authorization",
+        "te",
+        "trailer",
+        "transfer-encoding",
+        "upgrade",
+    ];
+
+    fn connection_listed(headers: &HeaderMap) -> Vec<HeaderName> {
+        headers
+            .get_all(CONNECTION)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+            .collect()
+    }
+
+    fn is_end_to_end(name: &HeaderName, listed: &[HeaderName]) -> bool {
+        !HOP_BY_HOP.contains(&name.as_str()) && !listed.contains(name)
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        let listed = connection_listed(headers);
+        ser.collect_map(
+            headers
+                .keys()
+                .filter(|k| is_end_to_end(k, &listed))
+                .map(|k| (k.as_str(), ToSeq(headers.get_all(k)))),
+        )
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut headers = crate::header_map::deserialize(de)?;
+        for name in connection_listed(&headers) {
+            headers.remove(name);
+        }
+        for name in HOP_BY_HOP {
+            headers.remove(*name);
+        }
+        Ok(headers)
+    }
+}
+
+/// For `http_serde::uri_raw::RawUri`, a `Uri` that remembers the exact string it was parsed from
+///
+/// `#[serde(with = "http_serde::uri_raw")]`
+///
+/// `Uri` can't be reconstructed byte-for-byte from its parts (e.g. `HTTP://x` is written out as `http://x/`),
+/// so `RawUri` keeps the original string. It's validated as a `Uri` when deserialized, and serialized exactly as it was received.
+pub mod uri_raw {
+    use alloc::borrow::ToOwned;
+    use alloc::string::String;
+    use alloc::string::ToString;
+    use core::convert::TryFrom;
+    use core::fmt;
+    use core::str::FromStr;
+    use http::Uri;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, Deserializer, Serializer};
+
+    /// A parsed `Uri` along with its original string
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RawUri {
+        raw: String,
+        uri: Uri,
+    }
+
+    impl RawUri {
+        /// The exact string the `Uri` was parsed from
+        pub fn as_str(&self) -> &str {
+            &self.raw
+        }
+
+        /// The parsed `Uri`
+        pub fn uri(&self) -> &Uri {
+            &self.uri
+        }
+
+        /// Discards the original string
+        pub fn into_uri(self) -> Uri {
+            self.uri
+        }
+    }
+
+    impl FromStr for RawUri {
+        type Err = http::uri::InvalidUri;
+
+        fn from_str(raw: &str) -> Result<Self, Self::Err> {
+            Ok(Self {
+                uri: raw.parse()?,
+                raw: raw.to_owned(),
+            })
+        }
+    }
+
+    impl TryFrom<String> for RawUri {
+        type Error = http::uri::InvalidUri;
+
+        fn try_from(raw: String) -> Result<Self, Self::Error> {
+            Ok(Self {
+                uri: raw.parse()?,
+                raw,
+            })
+        }
+    }
+
+    /// Uses `Uri`'s own formatting as the original string
+    impl From<Uri> for RawUri {
+        fn from(uri: Uri) -> Self {
+            Self {
+                raw: uri.to_string(),
+                uri,
+            }
+        }
+    }
+
+    impl From<RawUri> for Uri {
+        fn from(raw: RawUri) -> Self {
+            raw.uri
+        }
+    }
+
+    impl fmt::Display for RawUri {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.raw)
+        }
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &RawUri, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(&uri.raw)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<RawUri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(de)?;
+        match raw.parse() {
+            Ok(uri) => Ok(RawUri { raw, uri }),
+            Err(_) => Err(de::Error::invalid_value(
+                Unexpected::Str(&raw),
+                &"valid uri",
+            )),
+        }
+    }
+}
+
+/// For `cookie::CookieJar`, stored as `Set-Cookie` headers. Requires the `cookie` and `std` features.
+///
+/// `#[serde(with = "http_serde::cookie_jar")]`
+///
+/// The jar is serialized like an `http::HeaderMap` with one `set-cookie` value per cookie (sorted by name),
+/// so cookie attributes such as `Path` or `Expires` are kept. When deserializing, `Cookie` headers
+/// are accepted too. Restored cookies are added as originals, so they don't show up in the jar's `delta()`.
+///
+/// Signed and private cookies are out of scope: their values are stored as they are in the jar (already signed or encrypted),
+/// and are neither verified nor decrypted by this module.
+#[cfg(all(feature = "cookie", feature = "std"))]
+pub mod cookie_jar {
+    use alloc::borrow::ToOwned;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use cookie::{Cookie, CookieJar};
+    use http::header::{COOKIE, SET_COOKIE};
+    use http::{HeaderMap, HeaderValue};
+    use serde::{de, ser, Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(jar: &CookieJar, ser: S) -> Result<S::Ok, S::Error> {
+        let mut cookies: Vec<_> = jar.iter().collect();
+        cookies.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let mut headers = HeaderMap::with_capacity(cookies.len());
+        for cookie in cookies {
+            let val = HeaderValue::from_str(&cookie.to_string()).map_err(ser::Error::custom)?;
+            headers.append(SET_COOKIE, val);
+        }
+        crate::header_map::serialize(&headers, ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<CookieJar, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let headers = crate::header_map::deserialize(de)?;
+        let mut jar = CookieJar::new();
+        for val in headers.get_all(SET_COOKIE) {
+            let val = val.to_str().map_err(de::Error::custom)?;
+            jar.add_original(Cookie::parse(val.to_owned()).map_err(de::Error::custom)?);
+        }
+        for val in headers.get_all(COOKIE) {
+            let val = val.to_str().map_err(de::Error::custom)?;
+            for pair in val.split(';').filter(|p| !p.trim().is_empty()) {
+                jar.add_original(Cookie::parse(pair.trim().to_owned()).map_err(de::Error::custom)?);
+            }
+        }
+        Ok(jar)
+    }
+}
+
+/// For a `Vec<http::Method>` set, such as the `Allow` header, stored as bitflags
+///
+/// `#[serde(with = "http_serde::method_flags")]`
+///
+/// When all methods are standard, they're serialized as a single `u16` with these bits:
+///
+/// | Method  | Bit      |
+/// |---------|----------|
+/// | GET     | `1 << 0` |
+/// | POST    | `1 << 1` |
+/// | PUT     | `1 << 2` |
+/// | DELETE  | `1 << 3` |
+/// | HEAD    | `1 << 4` |
+/// | OPTIONS | `1 << 5` |
+/// | CONNECT | `1 << 6` |
+/// | PATCH   | `1 << 7` |
+/// | TRACE   | `1 << 8` |
+///
+/// If there are any custom methods, it falls back to a list of method names.
+/// In binary formats the two forms are told apart with an enum tag.
+///
+/// The flags don't keep the order or duplicates, so the methods are deserialized in the order of the table above.
+pub mod method_flags {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use http::Method;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::ser::Serialize;
+    use serde::{de, Deserializer, Serializer};
+
+    const FLAGS: [(Method, u16); 9] = [
+        (Method::GET, 1 << 0),
+        (Method::POST, 1 << 1),
+        (Method::PUT, 1 << 2),
+        (Method::DELETE, 1 << 3),
+        (Method::HEAD, 1 << 4),
+        (Method::OPTIONS, 1 << 5),
+        (Method::CONNECT, 1 << 6),
+        (Method::PATCH, 1 << 7),
+        (Method::TRACE, 1 << 8),
+    ];
+
+    /// Bits of all the methods, or `None` if any of them isn't standard
+    pub fn to_flags(methods: &[Method]) -> Option<u16> {
+        methods.iter().try_fold(0, |flags, method| {
+            FLAGS
+                .iter()
+                .find(|(m, _)| m == method)
+                .map(|(_, bit)| flags | bit)
+        })
+    }
+
+    /// Methods of the set bits, or `None` if there are unknown bits
+    pub fn from_flags(flags: u16) -> Option<Vec<Method>> {
+        let known = FLAGS.iter().fold(0, |all, (_, bit)| all | bit);
+        if flags & !known != 0 {
+            return None;
+        }
+        Some(
+            FLAGS
+                .iter()
+                .filter(|(_, bit)| flags & bit != 0)
+                .map(|(m, _)| m.clone())
+                .collect(),
+        )
+    }
+
+    struct Names<'a>(&'a [Method]);
+
+    impl Serialize for Names<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            ser.collect_seq(self.0.iter().map(Method::as_str))
+        }
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(methods: &[Method], ser: S) -> Result<S::Ok, S::Error> {
+        let is_human_readable = ser.is_human_readable();
+        match to_flags(methods) {
+            Some(flags) if is_human_readable => ser.serialize_u16(flags),
+            Some(flags) => ser.serialize_newtype_variant("MethodFlags", 0, "Flags", &flags),
+            None if is_human_readable => Names(methods).serialize(ser),
+            None => ser.serialize_newtype_variant("MethodFlags", 1, "Methods", &Names(methods)),
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "MethodFlags")]
+    enum Tagged {
+        Flags(u16),
+        Methods(Vec<String>),
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        Flags(u16),
+        Methods(Vec<String>),
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Vec<Method>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = if de.is_human_readable() {
+            match Untagged::deserialize(de)? {
+                Untagged::Flags(flags) => Tagged::Flags(flags),
+                Untagged::Methods(names) => Tagged::Methods(names),
+            }
+        } else {
+            Tagged::deserialize(de)?
+        };
+        match repr {
+            Tagged::Flags(flags) => from_flags(flags).ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Unsigned(flags.into()), &"method bitflags")
+            }),
+            Tagged::Methods(names) => names
+                .iter()
+                .map(|name| {
+                    name.parse().map_err(|_| {
+                        de::Error::invalid_value(Unexpected::Str(name), &"valid method name")
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// For `http::StatusCode`, with a flag telling whether the request can be retried
+///
+/// `#[serde(with = "http_serde::status_code_retry")]`
+///
+/// Serialized as `{"code": 503, "retryable": true}`. The flag is derived from the code, so it's ignored when deserializing.
+///
+/// Codes in [`DEFAULT_RETRYABLE`](status_code_retry::DEFAULT_RETRYABLE) are retryable. To use a different set,
+/// call [`serialize_with`](status_code_retry::serialize_with) from your own `serialize_with` function.
+pub mod status_code_retry {
+    use http::StatusCode;
+    use serde::de::Deserialize;
+    use serde::ser::SerializeStruct;
+    use serde::{Deserializer, Serializer};
+
+    /// 429 Too Many Requests, 502 Bad Gateway, 503 Service Unavailable, 504 Gateway Timeout
+    pub const DEFAULT_RETRYABLE: &[u16] = &[429, 502, 503, 504];
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        serialize_with(status, DEFAULT_RETRYABLE, ser)
+    }
+
+    /// Like `serialize`, but with a custom set of retryable codes
+    pub fn serialize_with<S: Serializer>(
+        status: &StatusCode,
+        retryable: &[u16],
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut s = ser.serialize_struct("StatusCode", 2)?;
+        s.serialize_field("code", &status.as_u16())?;
+        s.serialize_field("retryable", &retryable.contains(&status.as_u16()))?;
+        s.end()
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "StatusCode")]
+    struct Repr {
+        #[serde(with = "crate::status_code")]
+        code: StatusCode,
+        #[serde(default, rename = "retryable")]
+        _retryable: bool,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Repr::deserialize(de).map(|r| r.code)
+    }
+}
+
+/// For `http::Uri`, with the host split into registrable domain and subdomain. Requires the `psl` feature.
+///
+/// `#[serde(with = "http_serde::uri_psl")]`
+///
+/// `https://www.example.co.uk/x?y` is serialized as
+/// `{"scheme": "https", "subdomain": "www", "domain": "example.co.uk", "port": null, "path": "/x?y"}`
+/// (`path` includes the query string), and joined back into a `Uri` when deserialized.
+///
+/// The split uses Mozilla's [Public Suffix List](https://publicsuffix.org), as compiled into the [`psl`](https://lib.rs/psl) crate,
+/// so it's only as current as the version of `psl` you build with. Hosts that aren't under a known public suffix
+/// (IP addresses, `localhost`) are stored whole in `domain`. URIs without a host can't be serialized.
+/// User info (`user@`) in the authority isn't kept.
+#[cfg(feature = "psl")]
+pub mod uri_psl {
+    use alloc::string::String;
+    use alloc::string::ToString;
+    use http::Uri;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::ser::Serialize;
+    use serde::{de, ser, Deserializer, Serializer};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename = "Uri")]
+    struct Parts<S> {
+        scheme: Option<S>,
+        subdomain: Option<S>,
+        domain: S,
+        port: Option<u16>,
+        path: S,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        let host = uri
+            .host()
+            .ok_or_else(|| ser::Error::custom("uri has no host"))?;
+        // The PSL's wildcard rule would split an IP address like a domain
+        let is_ip = host.starts_with('[') || crate::uri_valid_host::is_ipv4(host);
+        let domain_len = match psl::domain_str(&host.to_ascii_lowercase()) {
+            Some(d) if !is_ip => d.len(),
+            _ => host.len(),
+        };
+        let (subdomain, domain) = host.split_at(host.len() - domain_len);
+        let subdomain = if subdomain.ends_with('.') {
+            Some(&subdomain[..subdomain.len() - 1])
+        } else {
+            None
+        };
+        Parts {
+            scheme: uri.scheme_str(),
+            subdomain,
+            domain,
+            port: uri.port_u16(),
+            path: uri.path_and_query().map(|p| p.as_str()).unwrap_or(""),
+        }
+        .serialize(ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let parts = Parts::<String>::deserialize(de)?;
+        let mut uri = String::new();
+        if let Some(scheme) = &parts.scheme {
+            uri.push_str(scheme);
+            uri.push_str("://");
+        }
+        if let Some(subdomain) = &parts.subdomain {
+            uri.push_str(subdomain);
+            uri.push('.');
+        }
+        uri.push_str(&parts.domain);
+        if let Some(port) = parts.port {
+            uri.push(':');
+            uri.push_str(&port.to_string());
+        }
+        uri.push_str(&parts.path);
+        uri.parse()
+            .map_err(|_| de::Error::invalid_value(Unexpected::Str(&uri), &"valid uri"))
+    }
+}
+
+/// For `http::HeaderMap`, with names in environment variable style
+///
+/// `#[serde(with = "http_serde::header_map_env")]`
+///
+/// Names are serialized in upper case with dashes replaced by underscores (`content-type` becomes `CONTENT_TYPE`),
+/// and turned back into lowercase, dashed header names when deserialized. This is ambiguous for names that contain
+/// underscores: `x_foo` and `x-foo` are both serialized as `X_FOO`, and always come back as `x-foo`.
+///
+/// Each name maps to a single string, like in an environment. Multiple values of the same header are joined with `, `,
+/// and aren't split again when deserializing. Values that aren't valid UTF-8 can't be serialized.
+pub mod header_map_env {
+    use alloc::borrow::Cow;
+    use alloc::collections::BTreeMap;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use http::header::HeaderName;
+    use http::{HeaderMap, HeaderValue};
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, ser, Deserializer, Serializer};
+
+    fn to_env(name: &HeaderName) -> String {
+        name.as_str().to_ascii_uppercase().replace('-', "_")
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        let mut vars = Vec::with_capacity(headers.keys_len());
+        for name in headers.keys() {
+            let mut joined = String::new();
+            for val in headers.get_all(name) {
+                let val = val.to_str().map_err(ser::Error::custom)?;
+                if !joined.is_empty() {
+                    joined.push_str(", ");
+                }
+                joined.push_str(val);
+            }
+            vars.push((to_env(name), joined));
+        }
+        ser.collect_map(vars)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let vars = BTreeMap::<Cow<str>, Cow<str>>::deserialize(de)?;
+        let mut map = HeaderMap::with_capacity(vars.len());
+        for (var, val) in &vars {
+            let name = var.to_ascii_lowercase().replace('_', "-");
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(var), &"header name"))?;
+            let val = HeaderValue::from_str(val)
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &"header value"))?;
+            map.append(name, val);
+        }
+        Ok(map)
+    }
+}
+
+/// For `http_serde::retry_after::RetryAfter`, the value of the `Retry-After` header. Requires the `retry_after` feature.
+///
+/// `#[serde(with = "http_serde::retry_after")]`
+///
+/// Serialized as the header's string form: either a number of seconds (`"120"`) or an HTTP-date (`"Wed, 21 Oct 2015 07:28:00 GMT"`).
+/// Both have a resolution of whole seconds, so fractions of a second are truncated.
+/// In human-readable formats a plain number of seconds is accepted too.
+#[cfg(feature = "retry_after")]
+pub mod retry_after {
+    use core::fmt;
+    use core::str::FromStr;
+    use serde::de::{Unexpected, Visitor};
+    use serde::{de, Deserializer, Serializer};
+    use std::time::{Duration, SystemTime};
+
+    /// Either a delay or a point in time after which to retry
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum RetryAfter {
+        /// `Retry-After: 120`
+        Delay(Duration),
+        /// `Retry-After: Wed, 21 Oct 2015 07:28:00 GMT`
+        At(SystemTime),
+    }
+
+    /// The header value isn't a number of seconds nor an HTTP-date
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct InvalidRetryAfter;
+
+    impl fmt::Display for InvalidRetryAfter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("invalid Retry-After value")
+        }
+    }
+
+    impl std::error::Error for InvalidRetryAfter {}
+
+    /// Parses the header value
+    impl FromStr for RetryAfter {
+        type Err = InvalidRetryAfter;
+
+        fn from_str(val: &str) -> Result<Self, Self::Err> {
+            let val = val.trim();
+            if !val.is_empty() && val.bytes().all(|b| b.is_ascii_digit()) {
+                let secs = val.parse().map_err(|_| InvalidRetryAfter)?;
+                return Ok(RetryAfter::Delay(Duration::from_secs(secs)));
+            }
+            httpdate::parse_http_date(val)
+                .map(RetryAfter::At)
+                .map_err(|_| InvalidRetryAfter)
+        }
+    }
+
+    /// Formats as the header value
+    impl fmt::Display for RetryAfter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match *self {
+                RetryAfter::Delay(delay) => write!(f, "{}", delay.as_secs()),
+                RetryAfter::At(time) => f.write_str(&httpdate::fmt_http_date(time)),
+            }
+        }
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(retry_after: &RetryAfter, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(retry_after)
+    }
+
+    struct RetryAfterVisitor;
+
+    impl<'de> Visitor<'de> for RetryAfterVisitor {
+        type Value = RetryAfter;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "number of seconds or HTTP-date")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            val.parse()
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))
+        }
+
+        fn visit_u64<E: de::Error>(self, val: u64) -> Result<Self::Value, E> {
+            Ok(RetryAfter::Delay(Duration::from_secs(val)))
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<RetryAfter, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if de.is_human_readable() {
+            de.deserialize_any(RetryAfterVisitor)
+        } else {
+            de.deserialize_str(RetryAfterVisitor)
+        }
+    }
+}
+
+/// For `http::HeaderMap`, rejecting values that aren't pure ASCII
+///
+/// `#[serde(with = "http_serde::header_map_ascii")]`
+///
+/// `HeaderValue` allows `obs-text` bytes (`0x80`-`0xFF`) for compatibility. This module is for strict conformance:
+/// deserialization fails if any value contains such a byte. Serialization is the same as `http_serde::header_map`.
+pub mod header_map_ascii {
+    use http::HeaderMap;
+    use serde::de::Unexpected;
+    use serde::{de, Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        crate::header_map::serialize(headers, ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let headers = crate::header_map::deserialize(de)?;
+        if let Some(val) = headers.values().find(|v| !v.as_bytes().is_ascii()) {
+            return Err(de::Error::invalid_value(
+                Unexpected::Bytes(val.as_bytes()),
+                &"ASCII header value",
+            ));
+        }
+        Ok(headers)
+    }
+}
+
+/// For `http::Uri`, converted through `url::Url`. Requires the `url` and `std` features.
+///
+/// `#[serde(with = "http_serde::uri_as_url")]`
+///
+/// The output is the same as serializing the equivalent `url::Url`, so it's normalized the WHATWG way
+/// (e.g. lowercase host, default port removed, `.` and `..` segments resolved). Deserialization parses the string as a `Url`
+/// first, and then converts it to a `Uri`.
+///
+/// Conversion fails for:
+///
+/// * relative URIs (`/path`) and authority-only ones (`example.com:443`), since `Url` must be absolute,
+/// * URLs that `Uri` can't represent, e.g. without a host (`mailto:x@example.com`, `data:...`).
+///
+/// A `#fragment` is dropped, because `Uri` doesn't store fragments.
+#[cfg(all(feature = "url", feature = "std"))]
+pub mod uri_as_url {
+    use alloc::borrow::Cow;
+    use alloc::string::ToString;
+    use http::Uri;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, ser, Deserializer, Serializer};
+    use url::Url;
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        let url = Url::parse(&uri.to_string()).map_err(ser::Error::custom)?;
+        ser.serialize_str(url.as_str())
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let val = Cow::<str>::deserialize(de)?;
+        let url = Url::parse(&val)
+            .map_err(|_| de::Error::invalid_value(Unexpected::Str(&val), &"absolute url"))?;
+        match url[..url::Position::AfterQuery].parse::<Uri>() {
+            // without a scheme the url has been misread as something else
+            Ok(uri) if uri.scheme_str() == Some(url.scheme()) => Ok(uri),
+            _ => Err(de::Error::invalid_value(
+                Unexpected::Str(&val),
+                &"url representable as uri",
+            )),
+        }
+    }
+}
+
+/// For `http::StatusCode`, with its reason phrase and a stable key for translations
+///
+/// `#[serde(with = "http_serde::status_code_i18n")]`
+///
+/// Serialized as `{"code": 404, "reason": "Not Found", "key": "not_found"}`. The key is the canonical reason in `snake_case`
+/// (apostrophes are dropped, so 418 is `im_a_teapot`). Codes without a canonical reason have `null` for both.
+///
+/// Only `code` is used when deserializing.
+pub mod status_code_i18n {
+    use alloc::string::String;
+    use http::StatusCode;
+    use serde::de::Deserialize;
+    use serde::ser::SerializeStruct;
+    use serde::{Deserializer, Serializer};
+
+    /// The `snake_case` key for the code's canonical reason
+    pub fn key(status: StatusCode) -> Option<String> {
+        let reason = status.canonical_reason()?;
+        let mut key = String::with_capacity(reason.len());
+        for c in reason.chars().filter(|&c| c != '\'') {
+            if c.is_ascii_alphanumeric() {
+                key.push(c.to_ascii_lowercase());
+            } else if !key.is_empty() && !key.ends_with('_') {
+                key.push('_');
+            }
+        }
+        if key.ends_with('_') {
+            key.pop();
+        }
+        Some(key)
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        let mut s = ser.serialize_struct("StatusCode", 3)?;
+        s.serialize_field("code", &status.as_u16())?;
+        s.serialize_field("reason", &status.canonical_reason())?;
+        s.serialize_field("key", &key(*status))?;
+        s.end()
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "StatusCode")]
+    struct Repr {
+        #[serde(with = "crate::status_code")]
+        code: StatusCode,
+        #[serde(default, rename = "reason")]
+        _reason: Option<String>,
+        #[serde(default, rename = "key")]
+        _key: Option<String>,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Repr::deserialize(de).map(|r| r.code)
+    }
+}
+
+/// For `http::HeaderMap` that must contain all of the given headers
+///
+/// Deserialization fails if any of the headers is missing. Otherwise it's the same as `http_serde::header_map`.
+/// The list is a runtime value, so call this from your own `deserialize_with` function:
+///
+/// ```rust
+/// # use http::HeaderMap;
+/// fn host_and_type<'de, D: serde::Deserializer<'de>>(de: D) -> Result<HeaderMap, D::Error> {
+///     http_serde::header_map_require(&["host", "content-type"]).deserialize(de)
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Message {
+///     #[serde(serialize_with = "http_serde::header_map::serialize", deserialize_with = "host_and_type")]
+///     headers: HeaderMap,
+/// }
+/// ```
+pub fn header_map_require<'a>(names: &'a [&'a str]) -> RequiredHeaders<'a> {
+    RequiredHeaders { names }
+}
+
+/// Created by [`header_map_require`]
+#[derive(Debug, Clone, Copy)]
+pub struct RequiredHeaders<'a> {
+    names: &'a [&'a str],
+}
+
+impl RequiredHeaders<'_> {
+    /// Same as `http_serde::header_map::serialize`
+    pub fn serialize<S: serde::Serializer>(
+        &self,
+        headers: &http::HeaderMap,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        header_map::serialize(headers, ser)
+    }
+
+    /// Fails if any of the required headers is missing
+    pub fn deserialize<'de, D>(&self, de: D) -> Result<http::HeaderMap, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let headers = header_map::deserialize(de)?;
+        if let Some(name) = self.names.iter().find(|&&n| !headers.contains_key(n)) {
+            return Err(serde::de::Error::custom(format_args!(
+                "missing required header `{}`",
+                name
+            )));
+        }
+        Ok(headers)
+    }
+}
+
+/// For `http::Method`, as a single byte. Only the standard methods are supported.
+///
+/// `#[serde(with = "http_serde::method_u8")]`
+///
+/// The numbering follows the order in which the `http` crate defines the methods, but is fixed here
+/// and won't change if `http` changes:
+///
+/// | Method  | Byte |
+/// |---------|------|
+/// | OPTIONS | 0    |
+/// | GET     | 1    |
+/// | POST    | 2    |
+/// | PUT     | 3    |
+/// | DELETE  | 4    |
+/// | HEAD    | 5    |
+/// | TRACE   | 6    |
+/// | CONNECT | 7    |
+/// | PATCH   | 8    |
+///
+/// Extension methods (e.g. `PROPFIND`) can't be serialized, and other bytes can't be deserialized.
+pub mod method_u8 {
+    use http::Method;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, ser, Deserializer, Serializer};
+
+    const METHODS: [Method; 9] = [
+        Method::OPTIONS,
+        Method::GET,
+        Method::POST,
+        Method::PUT,
+        Method::DELETE,
+        Method::HEAD,
+        Method::TRACE,
+        Method::CONNECT,
+        Method::PATCH,
+    ];
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(method: &Method, ser: S) -> Result<S::Ok, S::Error> {
+        match METHODS.iter().position(|m| m == method) {
+            Some(byte) => ser.serialize_u8(byte as u8),
+            None => Err(ser::Error::custom(format_args!(
+                "method `{}` has no byte representation",
+                method
+            ))),
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Method, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let byte = u8::deserialize(de)?;
+        METHODS.get(byte as usize).cloned().ok_or_else(|| {
+            de::Error::invalid_value(
+                Unexpected::Unsigned(byte.into()),
+                &"standard method byte 0-8",
+            )
+        })
+    }
+}
+
+/// For `http::Uri`, with query parameters sorted by name
+///
+/// `#[serde(with = "http_serde::uri_sorted_query")]`
+///
+/// Equivalent URIs with parameters in a different order serialize the same way, e.g. for use as cache keys.
+/// `?b=2&a=1` is serialized as `?a=1&b=2`.
+///
+/// Parameters are sorted by the raw (not percent-decoded) name, and the sort is stable: repeated names keep their
+/// relative order, since it may be meaningful (`?x=1&x=2` isn't the same as `?x=2&x=1`). Values are kept byte-for-byte.
+/// Empty parameters (from `&&`) are removed.
+///
+/// Deserialization is the same as `http_serde::uri`.
+pub mod uri_sorted_query {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use http::Uri;
+    use serde::{Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        let query = match uri.query() {
+            Some(query) => query,
+            None => return crate::uri::serialize(uri, ser),
+        };
+        let mut params: Vec<_> = query.split('&').filter(|p| !p.is_empty()).collect();
+        params.sort_by_key(|p| p.split('=').next());
+
+        let full = uri.to_string();
+        let before_query = &full[..full.len() - query.len() - 1];
+        if params.is_empty() {
+            ser.serialize_str(before_query)
+        } else {
+            ser.collect_str(&format_args!("{}?{}", before_query, params.join("&")))
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        crate::uri::deserialize(de)
+    }
+}
+
+/// For a list of `(HeaderName, HeaderValue)` in their original order, with a global index per entry
+///
+/// `#[serde(with = "http_serde::header_map_indexed")]`
+///
+/// `HeaderMap` groups values by name, so it can't tell that headers arrived as `A, B, A`. For that level of fidelity
+/// keep the headers in a `Vec<(HeaderName, HeaderValue)>` (it can be turned into a `HeaderMap` with `collect()` at any time).
+///
+/// Serialized as `[{"index": 0, "name": "a", "value": "1"}, {"index": 1, "name": "b", "value": "2"}, ...]`.
+/// When deserializing, the entries are put in the order of their `index`, so their order in the serialized
+/// sequence doesn't matter. Each index can be used only once.
+///
+/// Values that aren't valid UTF-8 are serialized as bytes, which are always used in binary formats.
+pub mod header_map_indexed {
+    use alloc::vec::Vec;
+    use http::header::HeaderName;
+    use http::HeaderValue;
+    use serde::de::Deserialize;
+    use serde::{de, Deserializer, Serializer};
+
+    #[derive(serde::Serialize)]
+    #[serde(rename = "Header")]
+    struct Entry<'a> {
+        index: usize,
+        #[serde(with = "crate::header_name")]
+        name: &'a HeaderName,
+        #[serde(with = "crate::header_value")]
+        value: &'a HeaderValue,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(
+        headers: &[(HeaderName, HeaderValue)],
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        ser.collect_seq(
+            headers
+                .iter()
+                .enumerate()
+                .map(|(index, (name, value))| Entry { index, name, value }),
+        )
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Header")]
+    struct EntryDe {
+        index: usize,
+        #[serde(with = "crate::header_name")]
+        name: HeaderName,
+        #[serde(with = "crate::header_value")]
+        value: HeaderValue,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Vec<(HeaderName, HeaderValue)>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut entries = Vec::<EntryDe>::deserialize(de)?;
+        entries.sort_by_key(|e| e.index);
+        if let Some(pair) = entries
+            .windows(2)
+            .find(|pair| pair[0].index == pair[1].index)
+        {
+            return Err(de::Error::custom(format_args!(
+                "duplicate header index {}",
+                pair[0].index
+            )));
+        }
+        Ok(entries.into_iter().map(|e| (e.name, e.value)).collect())
+    }
+}
+
+/// For `http_serde::status_code_or_any::StatusMatch`, a status code or `"default"` meaning any status
+///
+/// `#[serde(with = "http_serde::status_code_or_any")]`
+///
+/// As in OpenAPI response objects, `StatusMatch::Any` is serialized as `"default"`, and `StatusMatch::Exact` as a number.
+/// Numeric strings like `"404"` are accepted too.
+///
+/// Binary formats can't tell a string from a number, so there `Any` is serialized as `0`, which is never a valid status code.
+pub mod status_code_or_any {
+    use core::fmt;
+    use http::StatusCode;
+    use serde::de::{Unexpected, Visitor};
+    use serde::{de, Deserializer, Serializer};
+
+    /// A specific status code, or any
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum StatusMatch {
+        /// Only this status
+        Exact(StatusCode),
+        /// Any status (`"default"`)
+        Any,
+    }
+
+    impl StatusMatch {
+        /// Whether the status is matched by this
+        pub fn matches(&self, status: StatusCode) -> bool {
+            match *self {
+                StatusMatch::Exact(s) => s == status,
+                StatusMatch::Any => true,
+            }
+        }
+    }
+
+    impl From<StatusCode> for StatusMatch {
+        fn from(status: StatusCode) -> Self {
+            StatusMatch::Exact(status)
+        }
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(status: &StatusMatch, ser: S) -> Result<S::Ok, S::Error> {
+        match status {
+            StatusMatch::Exact(status) => ser.serialize_u16(status.as_u16()),
+            StatusMatch::Any if ser.is_human_readable() => ser.serialize_str("default"),
+            StatusMatch::Any => ser.serialize_u16(0),
+        }
+    }
+
+    struct StatusMatchVisitor;
+
+    impl<'de> Visitor<'de> for StatusMatchVisitor {
+        type Value = StatusMatch;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "valid status code or \"default\"")
+        }
+
+        fn visit_u64<E: de::Error>(self, val: u64) -> Result<Self::Value, E> {
+            if val == 0 {
+                return Ok(StatusMatch::Any);
+            }
+            if val > core::u16::MAX.into() {
+                return Err(de::Error::invalid_value(Unexpected::Unsigned(val), &self));
+            }
+            StatusCode::from_u16(val as u16)
+                .map(StatusMatch::Exact)
+                .map_err(|_| de::Error::invalid_value(Unexpected::Unsigned(val), &self))
+        }
+
+        fn visit_i64<E: de::Error>(self, val: i64) -> Result<Self::Value, E> {
+            if val < 0 {
+                return Err(de::Error::invalid_value(Unexpected::Signed(val), &self));
+            }
+            self.visit_u64(val as u64)
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            if val == "default" {
+                return Ok(StatusMatch::Any);
+            }
+            val.parse::<StatusCode>()
+                .map(StatusMatch::Exact)
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<StatusMatch, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if de.is_human_readable() {
+            de.deserialize_any(StatusMatchVisitor)
+        } else {
+            de.deserialize_u16(StatusMatchVisitor)
+        }
+    }
+}
+
+/// For `http::Uri`, with an internationalized host converted to punycode. Requires the `idna` feature.
+///
+/// `#[serde(with = "http_serde::uri_idna")]`
+///
+/// The host is normalized with [UTS #46](https://www.unicode.org/reports/tr46/) processing (as in browsers),
+/// so `https://例え.jp/path` is stored as `https://xn--r8jz45g.jp/path`. ASCII hosts are lowercased.
+/// Scheme, user info, port, path and query are kept as they are. IP literals are left alone.
+///
+/// `http::Uri` itself can't contain a non-ASCII host, so deserialization converts the host before parsing the string.
+/// That's the only way to get a `Uri` out of an IRI like `https://例え.jp/`.
+///
+/// Hosts that aren't valid IDNs (e.g. `xn--` labels with invalid punycode, or disallowed characters) are an error
+/// both when serializing and deserializing.
+#[cfg(feature = "idna")]
+pub mod uri_idna {
+    use alloc::borrow::Cow;
+    use alloc::borrow::ToOwned;
+    use alloc::format;
+    use alloc::string::String;
+    use http::Uri;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, ser, Deserializer, Serializer};
+
+    fn to_ascii_authority(authority: &str) -> Result<String, idna::Errors> {
+        let (userinfo, host_port) = match authority.rfind('@') {
+            Some(at) => authority.split_at(at + 1),
+            None => ("", authority),
+        };
+        if host_port.starts_with('[') {
+            return Ok(authority.to_owned());
+        }
+        let (host, port) = match host_port.rfind(':') {
+            Some(colon) => host_port.split_at(colon),
+            None => (host_port, ""),
+        };
+        Ok(format!(
+            "{}{}{}",
+            userinfo,
+            idna::domain_to_ascii(host)?,
+            port
+        ))
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        let authority = match uri.authority() {
+            Some(authority) => {
+                to_ascii_authority(authority.as_str()).map_err(ser::Error::custom)?
+            }
+            None => return crate::uri::serialize(uri, ser),
+        };
+        let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("");
+        match uri.scheme_str() {
+            Some(scheme) => ser.collect_str(&format_args!("{}://{}{}", scheme, authority, path)),
+            None => ser.collect_str(&format_args!("{}{}", authority, path)),
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let val = Cow::<str>::deserialize(de)?;
+        let ascii = if val.starts_with('/') || val.starts_with('*') {
+            Cow::Borrowed(&*val)
+        } else {
+            let start = val.find("://").map_or(0, |pos| pos + 3);
+            let end = val[start..]
+                .find(&['/', '?', '#'][..])
+                .map_or(val.len(), |end| start + end);
+            let authority = to_ascii_authority(&val[start..end])
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(&val), &"valid IDN host"))?;
+            Cow::Owned(format!("{}{}{}", &val[..start], authority, &val[end..]))
+        };
+        ascii
+            .parse()
+            .map_err(|_| de::Error::invalid_value(Unexpected::Str(&val), &"valid uri"))
+    }
+}
+
+/// For displaying `http::HeaderMap` with any bytes in values. Serialization only.
+///
+/// `#[serde(serialize_with = "http_serde::header_map_lossy_display::serialize")]`
+///
+/// Values are serialized as strings, like `http_serde::header_map` does in human-readable formats, but bytes that
+/// aren't valid UTF-8 are replaced with `U+FFFD` (`�`) instead of the value being skipped.
+///
+/// This is one-way: the replacement loses the original bytes, so the output can't be turned back into the same `HeaderMap`.
+/// To avoid storing corrupted values by accident, `deserialize` always fails.
+pub mod header_map_lossy_display {
+    use alloc::string::String;
+    use http::header::GetAll;
+    use http::{HeaderMap, HeaderValue};
+    use serde::ser::Serialize;
+    use serde::{de, Deserializer, Serializer};
+
+    struct Lossy<'a>(GetAll<'a, HeaderValue>);
+
+    impl Serialize for Lossy<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            let mut values = self.0.iter().map(|v| String::from_utf8_lossy(v.as_bytes()));
+            match (values.next(), values.next()) {
+                (Some(only), None) => ser.serialize_str(&only),
+                _ => ser.collect_seq(self.0.iter().map(|v| String::from_utf8_lossy(v.as_bytes()))),
+            }
+        }
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_map(
+            headers
+                .keys()
+                .map(|k| (k.as_str(), Lossy(headers.get_all(k)))),
+        )
+    }
+
+    /// Always fails, because the lossy form can't be deserialized faithfully
+    pub fn deserialize<'de, D>(_: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Err(de::Error::custom(
+            "header_map_lossy_display is serialize-only; its values may have lost bytes",
+        ))
+    }
+}
+
+/// For a `(http::Method, String)` route key, such as `"GET /users/:id"`
+///
+/// `#[serde(with = "http_serde::route_key")]`
+///
+/// The method and the URI template are joined with a space. When deserializing, the string is split only at the first space,
+/// so templates may contain spaces. The template isn't parsed as a `Uri`, because patterns like `{id}` aren't valid in one.
+pub mod route_key {
+    use alloc::borrow::ToOwned;
+    use alloc::string::String;
+    use core::fmt;
+    use http::Method;
+    use serde::de::{Unexpected, Visitor};
+    use serde::{de, Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(route: &(Method, String), ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(&format_args!("{} {}", route.0, route.1))
+    }
+
+    struct RouteKeyVisitor;
+
+    impl<'de> Visitor<'de> for RouteKeyVisitor {
+        type Value = (Method, String);
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "method and path separated by a space")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            let space = val
+                .find(' ')
+                .ok_or_else(|| de::Error::invalid_value(Unexpected::Str(val), &self))?;
+            let method = val[..space]
+                .parse()
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(val), &self))?;
+            Ok((method, val[space + 1..].to_owned()))
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<(Method, String), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        de.deserialize_str(RouteKeyVisitor)
+    }
+}
+
+/// For `http::Response`, with a weak `ETag` computed from the body. Requires the `etag` feature.
+///
+/// `#[serde(with = "http_serde::response_with_etag")]`
+///
+/// The response is serialized as `{"status": 200, "version": "HTTP/1.1", "headers": {...}, "body": ...}`, using the same
+/// representation as the other modules. The body can be any type that is `AsRef<[u8]>` and `From<Vec<u8>>`, like `Vec<u8>`
+/// or `bytes::Bytes`, and is serialized as a string if it's valid UTF-8 in human-readable formats, and as bytes otherwise.
+/// Extensions aren't serialized.
+///
+/// The `ETag` is `W/"fnv-<hex>"`, where `<hex>` is the 64-bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hash of the
+/// body in 16 lowercase hex digits. FNV is fast and stable across platforms and versions, but isn't a cryptographic hash,
+/// so this only guards against accidental changes. When serializing, the `ETag` is added if there isn't one,
+/// and recomputed if it's one of these. An `ETag` in any other format (e.g. from the origin server) is kept untouched.
+/// When deserializing, an `ETag` in this format must match the body.
+#[cfg(feature = "etag")]
+pub mod response_with_etag {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::fmt;
+    use core::hash::Hasher;
+    use http::header::ETAG;
+    use http::{HeaderMap, HeaderValue, Response, StatusCode, Version};
+    use serde::de::{Deserialize, SeqAccess, Unexpected, Visitor};
+    use serde::ser::Serialize;
+    use serde::{de, Deserializer, Serializer};
+
+    const PREFIX: &str = "W/\"fnv-";
+
+    /// The weak `ETag` for this body
+    pub fn etag(body: &[u8]) -> HeaderValue {
+        let mut hasher = fnv::FnvHasher::default();
+        hasher.write(body);
+        let etag = format!("{}{:016x}\"", PREFIX, hasher.finish());
+        HeaderValue::from_str(&etag).expect("hex is a valid header value")
+    }
+
+    fn is_computed(etag: &HeaderValue) -> bool {
+        etag.as_bytes().starts_with(PREFIX.as_bytes())
+    }
+
+    struct Body<'a>(&'a [u8]);
+
+    impl Serialize for Body<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            match core::str::from_utf8(self.0) {
+                Ok(s) if ser.is_human_readable() => ser.serialize_str(s),
+                _ => ser.serialize_bytes(self.0),
+            }
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename = "Response")]
+    struct ResponseSer<'a> {
+        #[serde(with = "crate::status_code")]
+        status: StatusCode,
+        #[serde(with = "crate::version")]
+        version: Version,
+        #[serde(with = "crate::header_map")]
+        headers: HeaderMap,
+        body: Body<'a>,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<B, S>(res: &Response<B>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        B: AsRef<[u8]>,
+        S: Serializer,
+    {
+        let body = res.body().as_ref();
+        let mut headers = res.headers().clone();
+        match headers.get(ETAG) {
+            Some(found) if !is_computed(found) => {}
+            _ => {
+                headers.insert(ETAG, etag(body));
+            }
+        }
+        ResponseSer {
+            status: res.status(),
+            version: res.version(),
+            headers,
+            body: Body(body),
+        }
+        .serialize(ser)
+    }
+
+    struct BodyDe(Vec<u8>);
+
+    struct BodyVisitor;
+
+    impl<'de> Visitor<'de> for BodyVisitor {
+        type Value = BodyDe;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "body string or bytes")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            Ok(BodyDe(val.as_bytes().to_vec()))
+        }
+
+        fn visit_string<E: de::Error>(self, val: String) -> Result<Self::Value, E> {
+            Ok(BodyDe(val.into_bytes()))
+        }
+
+        fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+            Ok(BodyDe(val.to_vec()))
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, val: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(BodyDe(val))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(b) = seq.next_element()? {
+                bytes.push(b);
+            }
+            Ok(BodyDe(bytes))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BodyDe {
+        fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            if de.is_human_readable() {
+                de.deserialize_any(BodyVisitor)
+            } else {
+                de.deserialize_byte_buf(BodyVisitor)
+            }
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Response")]
+    struct ResponseDe {
+        #[serde(with = "crate::status_code")]
+        status: StatusCode,
+        #[serde(with = "crate::version")]
+        version: Version,
+        #[serde(with = "crate::header_map")]
+        headers: HeaderMap,
+        body: BodyDe,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, B, D>(de: D) -> Result<Response<B>, D::Error>
+    where
+        B: From<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        let parts = ResponseDe::deserialize(de)?;
+        let body = parts.body.0;
+        if let Some(found) = parts.headers.get(ETAG).filter(|e| is_computed(e)) {
+            if *found != etag(&body) {
+                return Err(de::Error::invalid_value(
+                    Unexpected::Bytes(found.as_bytes()),
+                    &"ETag matching the body",
+                ));
+            }
+        }
+        let mut res = Response::new(B::from(body));
+        *res.status_mut() = parts.status;
+        *res.version_mut() = parts.version;
+        *res.headers_mut() = parts.headers;
+        Ok(res)
+    }
+}
+
+/// For `http::HeaderMap`, as a fixed list of values matching a log format
+///
+/// `#[serde(with = "http_serde::header_map_logfmt")]`
+///
+/// Only the headers in [`DEFAULT_FIELDS`](header_map_logfmt::DEFAULT_FIELDS) are serialized, in that order, as an array
+/// like `["curl/8.0", null, "example.com"]`. Missing headers are `null`, multiple values are joined with `, `,
+/// and all other headers are left out. Deserialization gives a `HeaderMap` with only these headers.
+///
+/// For a different list of headers, call [`serialize_with`](header_map_logfmt::serialize_with) and
+/// [`deserialize_with`](header_map_logfmt::deserialize_with) from your own functions.
+pub mod header_map_logfmt {
+    use alloc::borrow::Cow;
+    use alloc::borrow::ToOwned;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use http::header::HeaderName;
+    use http::{HeaderMap, HeaderValue};
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, ser, Deserializer, Serializer};
+
+    /// `User-Agent`, `Referer`, `Host`
+    pub const DEFAULT_FIELDS: &[&str] = &["user-agent", "referer", "host"];
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        serialize_with(headers, DEFAULT_FIELDS, ser)
+    }
+
+    /// Like `serialize`, but with a custom list of headers
+    pub fn serialize_with<S: Serializer>(
+        headers: &HeaderMap,
+        fields: &[&str],
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut values = Vec::with_capacity(fields.len());
+        for &field in fields {
+            let mut joined: Option<String> = None;
+            for val in headers.get_all(field) {
+                let val = val.to_str().map_err(ser::Error::custom)?;
+                match &mut joined {
+                    Some(joined) => {
+                        joined.push_str(", ");
+                        joined.push_str(val);
+                    }
+                    None => joined = Some(val.to_owned()),
+                }
+            }
+            values.push(joined);
+        }
+        ser.collect_seq(values)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_with(DEFAULT_FIELDS, de)
+    }
+
+    /// Like `deserialize`, but with a custom list of headers
+    pub fn deserialize_with<'de, D>(fields: &[&str], de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let values = Vec::<Option<Cow<str>>>::deserialize(de)?;
+        if values.len() != fields.len() {
+            return Err(de::Error::invalid_length(
+                values.len(),
+                &&*format!("{} log fields", fields.len()),
+            ));
+        }
+        let mut map = HeaderMap::new();
+        for (&field, val) in fields.iter().zip(values) {
+            if let Some(val) = val {
+                let name = HeaderName::from_bytes(field.as_bytes()).map_err(de::Error::custom)?;
+                let val = HeaderValue::from_str(&val).map_err(|_| {
+                    de::Error::invalid_value(Unexpected::Str(&val), &"header value")
+                })?;
+                map.insert(name, val);
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// For `http_serde::uri_raw::RawUri`, keeping an empty path distinct from `/`
+///
+/// `#[serde(with = "http_serde::uri_exact_path")]`
+///
+/// `http::Uri` doesn't tell `http://x` from `http://x/` (both have the path `/`), so `http_serde::uri` serializes both as `http://x/`.
+/// Where the difference matters, store the URI as a `RawUri`, which keeps the string it was parsed from.
+/// This is the same as `http_serde::uri_raw`.
+pub mod uri_exact_path {
+    pub use crate::uri_raw::{deserialize, serialize, RawUri};
+}
+
+/// For `http::StatusCode`, as the skeleton of an RFC 7807 Problem Details object
+///
+/// `#[serde(with = "http_serde::status_code_problem")]`
+///
+/// Serialized as `{"status": 404, "title": "Not Found"}`, with the canonical reason as the title
+/// (`null` for codes that don't have one). Only `status` is used when deserializing.
+///
+/// To add `type`, `detail` and other members, use `#[serde(flatten)]` on the field in your problem struct.
+pub mod status_code_problem {
+    use alloc::string::String;
+    use http::StatusCode;
+    use serde::de::Deserialize;
+    use serde::ser::SerializeStruct;
+    use serde::{Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        let mut s = ser.serialize_struct("Problem", 2)?;
+        s.serialize_field("status", &status.as_u16())?;
+        s.serialize_field("title", &status.canonical_reason())?;
+        s.end()
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Problem")]
+    struct Repr {
+        #[serde(with = "crate::status_code")]
+        status: StatusCode,
+        #[serde(default, rename = "title")]
+        _title: Option<String>,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Repr::deserialize(de).map(|r| r.status)
+    }
+}
+
+/// For `http::HeaderMap`, as a raw `Name: Value\r\n` header block
+///
+/// `#[serde(with = "http_serde::header_map_raw_block")]`
+///
+/// Serialized as the block of header lines as they'd appear in an HTTP/1.1 message, with lowercase names,
+/// values as they are, and terminated by an empty line: `host: example.com\r\naccept: */*\r\n\r\n`.
+/// The block is a string in human-readable formats if it's valid UTF-8, and bytes otherwise.
+///
+/// When deserializing, bare `\n` line endings and a missing final empty line are accepted,
+/// and obsolete line folding (continuation lines starting with a space or tab) is replaced with a single space.
+/// Anything after the empty line is an error.
+pub mod header_map_raw_block {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::fmt;
+    use http::header::HeaderName;
+    use http::{HeaderMap, HeaderValue};
+    use serde::de::{SeqAccess, Unexpected, Visitor};
+    use serde::{de, Deserializer, Serializer};
+
+    /// The header block as bytes
+    pub fn to_bytes(headers: &HeaderMap) -> Vec<u8> {
+        let mut block = Vec::new();
+        for (name, val) in headers {
+            block.extend_from_slice(name.as_str().as_bytes());
+            block.extend_from_slice(b": ");
+            block.extend_from_slice(val.as_bytes());
+            block.extend_from_slice(b"\r\n");
+        }
+        block.extend_from_slice(b"\r\n");
+        block
+    }
+
+    fn trim(mut bytes: &[u8]) -> &[u8] {
+        while let [b' ' | b'\t', rest @ ..] = bytes {
+            bytes = rest;
+        }
+        while let [rest @ .., b' ' | b'\t'] = bytes {
+            bytes = rest;
+        }
+        bytes
+    }
+
+    /// Parses the header block
+    pub fn from_bytes(block: &[u8]) -> Result<HeaderMap, String> {
+        let mut lines: Vec<(&[u8], Vec<u8>)> = Vec::new();
+        let mut rest = block;
+        while !rest.is_empty() {
+            let (line, next) = match rest.iter().position(|&b| b == b'\n') {
+                Some(nl) => (&rest[..nl], &rest[nl + 1..]),
+                None => (rest, &[][..]),
+            };
+            rest = next;
+            let line = if line.ends_with(b"\r") {
+                &line[..line.len() - 1]
+            } else {
+                line
+            };
+            if line.is_empty() {
+                if !rest.is_empty() {
+                    return Err("data after the end of the header block".into());
+                }
+                break;
+            }
+            if line[0] == b' ' || line[0] == b'\t' {
+                let (_, val) = lines
+                    .last_mut()
+                    .ok_or("header block starts with a continuation line")?;
+                val.push(b' ');
+                val.extend_from_slice(trim(line));
+                continue;
+            }
+            let colon = line
+                .iter()
+                .position(|&b| b == b':')
+                .ok_or_else(|| format!("no colon in line {:?}", String::from_utf8_lossy(line)))?;
+            lines.push((&line[..colon], trim(&line[colon + 1..]).to_vec()));
+        }
+
+        let mut map = HeaderMap::with_capacity(lines.len());
+        for (name, val) in lines {
+            let name = HeaderName::from_bytes(name)
+                .map_err(|_| format!("invalid header name {:?}", String::from_utf8_lossy(name)))?;
+            let val = HeaderValue::from_bytes(&val)
+                .map_err(|_| format!("invalid value of header {}", name))?;
+            map.append(name, val);
+        }
+        Ok(map)
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        let block = to_bytes(headers);
+        match core::str::from_utf8(&block) {
+            Ok(s) if ser.is_human_readable() => ser.serialize_str(s),
+            _ => ser.serialize_bytes(&block),
+        }
+    }
+
+    struct BlockVisitor;
+
+    impl<'de> Visitor<'de> for BlockVisitor {
+        type Value = HeaderMap;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "HTTP header block")
+        }
+
+        fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<Self::Value, E> {
+            from_bytes(val).map_err(|e| de::Error::invalid_value(Unexpected::Other(&e), &self))
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+            self.visit_bytes(val.as_bytes())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut block = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(b) = seq.next_element()? {
+                block.push(b);
+            }
+            self.visit_bytes(&block)
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if de.is_human_readable() {
+            de.deserialize_any(BlockVisitor)
+        } else {
+            de.deserialize_byte_buf(BlockVisitor)
+        }
+    }
+}
+
+/// For `http::Version`, as a protocol name and version number pair
+///
+/// `#[serde(with = "http_serde::version_protocol_tagged")]`
+///
+/// Serialized as `{"protocol": "http", "version": "1.1"}`. The protocol is always `"http"`, and the versions are
+/// `"0.9"`, `"1.0"`, `"1.1"`, `"2"` and `"3"`. Unknown versions are an error in both directions.
+pub mod version_protocol_tagged {
+    use alloc::borrow::Cow;
+    use http::Version;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::ser::SerializeStruct;
+    use serde::{de, ser, Deserializer, Serializer};
+
+    const VERSIONS: [(Version, &str); 5] = [
+        (Version::HTTP_09, "0.9"),
+        (Version::HTTP_10, "1.0"),
+        (Version::HTTP_11, "1.1"),
+        (Version::HTTP_2, "2"),
+        (Version::HTTP_3, "3"),
+    ];
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(version: &Version, ser: S) -> Result<S::Ok, S::Error> {
+        let number = VERSIONS
+            .iter()
+            .find(|(v, _)| v == version)
+            .map(|(_, number)| *number)
+            .ok_or_else(|| ser::Error::custom(format_args!("unknown version {:?}", version)))?;
+        let mut s = ser.serialize_struct("Version", 2)?;
+        s.serialize_field("protocol", "http")?;
+        s.serialize_field("version", number)?;
+        s.end()
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Version")]
+    struct Repr<'a> {
+        #[serde(borrow)]
+        protocol: Cow<'a, str>,
+        #[serde(borrow)]
+        version: Cow<'a, str>,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Version, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = Repr::deserialize(de)?;
+        if repr.protocol != "http" {
+            return Err(de::Error::invalid_value(
+                Unexpected::Str(&repr.protocol),
+                &"\"http\"",
+            ));
+        }
+        VERSIONS
+            .iter()
+            .find(|(_, number)| *number == repr.version)
+            .map(|(v, _)| *v)
+            .ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Str(&repr.version), &"HTTP version number")
+            })
+    }
+}
+
+/// For `http::Uri`, rejecting hosts that aren't a well-formed DNS name or IP address
+///
+/// `#[serde(with = "http_serde::uri_valid_host")]`
+///
+/// `Uri` accepts many hosts that can't possibly be resolved, like `exa%20mple` or `-x-.com`. After parsing, this checks
+/// that the host is an IPv4 address, a bracketed IPv6 address, or a DNS name made of letters, digits and hyphens
+/// (labels of 1-63 characters not starting or ending with a hyphen, at most 253 characters in total, the last label not all digits).
+/// This is only a syntax check, no DNS lookup is made. URIs without a host (e.g. `/path`) are accepted.
+///
+/// Serialization is the same as `http_serde::uri`.
+pub mod uri_valid_host {
+    use http::Uri;
+    use serde::de::Unexpected;
+    use serde::{de, Deserializer, Serializer};
+
+    /// Four decimal numbers 0-255 without leading zeros, separated by dots
+    pub(crate) fn is_ipv4(host: &str) -> bool {
+        let mut parts = 0;
+        let valid = host.split('.').all(|part| {
+            parts += 1;
+            !part.is_empty()
+                && part.len() <= 3
+                && (part.len() == 1 || !part.starts_with('0'))
+                && part.bytes().all(|b| b.is_ascii_digit())
+                && part.parse::<u8>().is_ok()
+        });
+        valid && parts == 4
+    }
+
+    /// Groups of up to 4 hex digits, with at most one `::` and optionally an IPv4 address at the end
+    fn is_ipv6(addr: &str) -> bool {
+        // Number of 16-bit groups, or `None` if malformed
+        fn groups(part: &str, ipv4_allowed: bool) -> Option<usize> {
+            if part.is_empty() {
+                return Some(0);
+            }
+            let mut count = 0;
+            let mut pieces = part.split(':').peekable();
+            while let Some(piece) = pieces.next() {
+                if ipv4_allowed && pieces.peek().is_none() && piece.contains('.') {
+                    if !is_ipv4(piece) {
+                        return None;
+                    }
+                    count += 2;
+                } else if !piece.is_empty()
+                    && piece.len() <= 4
+                    && piece.bytes().all(|b| b.is_ascii_hexdigit())
+                {
+                    count += 1;
+                } else {
+                    return None;
+                }
+            }
+            Some(count)
+        }
+
+        match addr.find("::") {
+            Some(i) => {
+                let tail = &addr[i + 2..];
+                if tail.contains("::") {
+                    return false;
+                }
+                match (groups(&addr[..i], false), groups(tail, true)) {
+                    (Some(head), Some(tail)) => head + tail < 8,
+                    _ => false,
+                }
+            }
+            None => groups(addr, true) == Some(8),
+        }
+    }
+
+    /// Whether the host is a well-formed DNS name, IPv4 address, or IPv6 address in brackets
+    pub fn is_valid_host(host: &str) -> bool {
+        if host.starts_with('[') && host.ends_with(']') {
+            return is_ipv6(&host[1..host.len() - 1]);
+        }
+        if is_ipv4(host) {
+            return true;
+        }
+        let name = if host.ends_with('.') {
+            &host[..host.len() - 1]
+        } else {
+            host
+        };
+        if name.is_empty() || name.len() > 253 {
+            return false;
+        }
+        let valid_labels = name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        });
+        let tld = name.rsplit('.').next().unwrap_or(name);
+        valid_labels && !tld.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        crate::uri::serialize(uri, ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let uri = crate::uri::deserialize(de)?;
+        match uri.host() {
+            Some(host) if !is_valid_host(host) => Err(de::Error::invalid_value(
+                Unexpected::Str(host),
+                &"DNS name or IP address",
+            )),
+            _ => Ok(uri),
+        }
+    }
+}
+
+/// How [`header_map_dup`] handles a header name that appears more than once in the serialized map
+///
+/// This can happen with hand-written JSON like `{"x": "a", "x": "b"}`. Values listed in one array are never duplicates.
+///
+/// Plain `http_serde::header_map` doesn't take a strategy, and keeps its existing behavior: a repeated name with
+/// a single value replaces the earlier values (so it's `"b"` above), and a repeated name with an array is appended.
+/// `DupStrategy::default()` only applies to `header_map_dup`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DupStrategy {
+    /// Keep all values (the default for `header_map_dup`)
+    Append,
+    /// Keep only the values of the first occurrence
+    FirstWins,
+    /// Keep only the values of the last occurrence
+    LastWins,
+    /// Fail deserialization
+    Error,
+}
+
+impl Default for DupStrategy {
+    fn default() -> Self {
+        DupStrategy::Append
+    }
+}
+
+/// For `http::HeaderMap`, with a choice of how duplicate names are handled when deserializing
+///
+/// Otherwise it's the same as `http_serde::header_map`. Call this from your own `deserialize_with` function:
+///
+/// ```rust
+/// # use http::HeaderMap;
+/// use http_serde::DupStrategy;
+///
+/// fn strict<'de, D: serde::Deserializer<'de>>(de: D) -> Result<HeaderMap, D::Error> {
+///     http_serde::header_map_dup(DupStrategy::Error).deserialize(de)
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Message {
+///     #[serde(serialize_with = "http_serde::header_map::serialize", deserialize_with = "strict")]
+///     headers: HeaderMap,
+/// }
+/// ```
+pub fn header_map_dup(strategy: DupStrategy) -> HeaderMapDup {
+    HeaderMapDup { strategy }
+}
+
+/// Created by [`header_map_dup`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeaderMapDup {
+    strategy: DupStrategy,
+}
+
+impl HeaderMapDup {
+    /// Same as `http_serde::header_map::serialize`
+    pub fn serialize<S: serde::Serializer>(
+        &self,
+        headers: &http::HeaderMap,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        header_map::serialize(headers, ser)
+    }
+
+    /// Applies the strategy to duplicate names
+    pub fn deserialize<'de, D>(&self, de: D) -> Result<http::HeaderMap, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let is_human_readable = de.is_human_readable();
+        de.deserialize_map(header_map::HeaderMapVisitor {
+            is_human_readable,
+            duplicates: Some(self.strategy),
+        })
+    }
+}
+
+/// For `http::Method`, with a flag telling whether it's one of the standard methods
+///
+/// `#[serde(with = "http_serde::method_classified")]`
+///
+/// Serialized as `{"method": "PROPFIND", "standard": false}`. `standard` is `true` for the methods defined in
+/// RFC 7231 and RFC 5789: `GET`, `HEAD`, `POST`, `PUT`, `DELETE`, `CONNECT`, `OPTIONS`, `TRACE` and `PATCH`.
+/// The flag is derived from the method, so only `method` is used when deserializing.
+pub mod method_classified {
+    use http::Method;
+    use serde::de::Deserialize;
+    use serde::ser::SerializeStruct;
+    use serde::{Deserializer, Serializer};
+
+    /// Whether the method is one of the 9 standard ones
+    pub fn is_standard(method: &Method) -> bool {
+        [
+            Method::GET,
+            Method::HEAD,
+            Method::POST,
+            Method::PUT,
+            Method::DELETE,
+            Method::CONNECT,
+            Method::OPTIONS,
+            Method::TRACE,
+            Method::PATCH,
+        ]
+        .contains(method)
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(method: &Method, ser: S) -> Result<S::Ok, S::Error> {
+        let mut s = ser.serialize_struct("Method", 2)?;
+        s.serialize_field("method", method.as_str())?;
+        s.serialize_field("standard", &is_standard(method))?;
+        s.end()
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Method")]
+    struct Repr {
+        #[serde(with = "crate::method")]
+        method: Method,
+        #[serde(default, rename = "standard")]
+        _standard: bool,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Method, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Repr::deserialize(de).map(|r| r.method)
+    }
+}
+
+/// For `http::StatusCode`, as a signed integer, e.g. for an `INTEGER` database column
+///
+/// `#[serde(with = "http_serde::status_code_i32")]`
+///
+/// Serialized with `serialize_i32`. Deserialization accepts only codes in the 100-599 range, so negative numbers and
+/// the unassigned 600-999 range that `StatusCode` otherwise allows are rejected.
+pub mod status_code_i32 {
+    use http::StatusCode;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_i32(status.as_u16().into())
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code = i32::deserialize(de)?;
+        if !(100..=599).contains(&code) {
+            return Err(de::Error::invalid_value(
+                Unexpected::Signed(code.into()),
+                &"status code in range 100-599",
+            ));
+        }
+        StatusCode::from_u16(code as u16).map_err(de::Error::custom)
+    }
+}
+
+/// For `http::Uri` that must use `https`
+///
+/// `#[serde(with = "http_serde::uri_https_only")]`
+///
+/// Deserialization fails for any other scheme, and for URIs without a scheme (such as `/relative`).
+/// For `http` URIs the error message suggests the `https` equivalent, to help migrating stored values.
+///
+/// Serialization is the same as `http_serde::uri`.
+pub mod uri_https_only {
+    use alloc::string::ToString;
+    use http::uri::Scheme;
+    use http::Uri;
+    use serde::{de, Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        crate::uri::serialize(uri, ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let uri = crate::uri::deserialize(de)?;
+        match uri.scheme() {
+            Some(scheme) if *scheme == Scheme::HTTPS => Ok(uri),
+            Some(scheme) if *scheme == Scheme::HTTP => Err(de::Error::custom(format_args!(
+                "`{}` is not https; upgrade it to `https{}`",
+                uri,
+                &uri.to_string()[4..]
+            ))),
+            Some(scheme) => Err(de::Error::custom(format_args!(
+                "`{}` uses `{}` instead of https",
+                uri, scheme
+            ))),
+            None => Err(de::Error::custom(format_args!(
+                "`{}` has no scheme; use an absolute https:// URI",
+                uri
+            ))),
+        }
+    }
+}
+
+/// How [`header_map_shaped`] writes the values of each header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeaderMapShape {
+    /// A string for a single value, an array for multiple values. Same as `http_serde::header_map`.
+    Auto,
+    /// Always an array, even for a single value
+    AlwaysArray,
+    /// Always a single string, with multiple values joined by `, `
+    AlwaysScalarJoin,
+}
+
+impl Default for HeaderMapShape {
+    fn default() -> Self {
+        HeaderMapShape::Auto
+    }
+}
+
+/// For `http::HeaderMap`, with an explicit choice of how values are written
+///
+/// The shape only applies to human-readable formats. Binary formats always use the same representation as `http_serde::header_map`.
+///
+/// All shapes are deserialized the same way: both strings and arrays are accepted. Joined values are kept as a single value,
+/// and aren't split at commas again, because that's not safe for all headers (e.g. `Set-Cookie` or dates).
+/// `AlwaysScalarJoin` can't serialize values that aren't valid UTF-8.
+///
+/// ```rust
+/// # use http::HeaderMap;
+/// use http_serde::HeaderMapShape;
+///
+/// fn arrays<S: serde::Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+///     http_serde::header_map_shaped(HeaderMapShape::AlwaysArray).serialize(headers, ser)
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Message {
+///     #[serde(serialize_with = "arrays", deserialize_with = "http_serde::header_map::deserialize")]
+///     headers: HeaderMap,
+/// }
+/// ```
+pub fn header_map_shaped(shape: HeaderMapShape) -> HeaderMapShaped {
+    HeaderMapShaped { shape }
+}
+
+/// Created by [`header_map_shaped`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeaderMapShaped {
+    shape: HeaderMapShape,
+}
+
+impl HeaderMapShaped {
+    /// Writes values in the chosen shape
+    pub fn serialize<S: serde::Serializer>(
+        &self,
+        headers: &http::HeaderMap,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        struct ShapedValues<'a> {
+            headers: &'a http::HeaderMap,
+            name: &'a http::header::HeaderName,
+            shape: HeaderMapShape,
+        }
+
+        struct Bytes<'a>(&'a [u8]);
+
+        impl serde::Serialize for Bytes<'_> {
+            fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                ser.serialize_bytes(self.0)
+            }
+        }
+
+        impl serde::Serialize for ShapedValues<'_> {
+            fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                let values = self.headers.get_all(self.name);
+                if !ser.is_human_readable() || self.shape == HeaderMapShape::Auto {
+                    return header_map::ToSeq(values).serialize(ser);
+                }
+                let strings: Result<Vec<&str>, _> = values.iter().map(|v| v.to_str()).collect();
+                match (strings, self.shape) {
+                    (Ok(strings), HeaderMapShape::AlwaysScalarJoin) => {
+                        ser.serialize_str(&strings.join(", "))
+                    }
+                    (Ok(strings), _) => ser.collect_seq(strings),
+                    (Err(_), HeaderMapShape::AlwaysArray) => {
+                        ser.collect_seq(values.iter().map(|v| Bytes(v.as_bytes())))
+                    }
+                    (Err(e), _) => Err(serde::ser::Error::custom(format_args!(
+                        "can't join values of `{}`: {}",
+                        self.name, e
+                    ))),
+                }
+            }
+        }
+
+        ser.collect_map(headers.keys().map(|name| {
+            (
+                name.as_str(),
+                ShapedValues {
+                    headers,
+                    name,
+                    shape: self.shape,
+                },
+            )
+        }))
+    }
+
+    /// Same as `http_serde::header_map::deserialize`
+    pub fn deserialize<'de, D>(&self, de: D) -> Result<http::HeaderMap, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        header_map::deserialize(de)
+    }
+}
+
+/// For `http::Version`, with a flag telling whether the version multiplexes requests over one connection
+///
+/// `#[serde(with = "http_serde::version_capabilities")]`
+///
+/// Serialized as `{"version": "HTTP/2.0", "multiplexed": true}`. The version uses the same format as
+/// `http_serde::version`, and `multiplexed` is `true` for HTTP/2 and HTTP/3. The flag is ignored when deserializing.
+pub mod version_capabilities {
+    use http::Version;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Whether the version can serve multiple requests concurrently on one connection
+    pub fn is_multiplexed(version: Version) -> bool {
+        version == Version::HTTP_2 || version == Version::HTTP_3
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Version")]
+    struct Repr {
+        #[serde(with = "crate::version")]
+        version: Version,
+        #[serde(default)]
+        multiplexed: bool,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(version: &Version, ser: S) -> Result<S::Ok, S::Error> {
+        Repr {
+            version: *version,
+            multiplexed: is_multiplexed(*version),
+        }
+        .serialize(ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Version, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Repr::deserialize(de).map(|r| r.version)
+    }
+}
+
+/// For `http::request::Parts`
+///
+/// `#[serde(with = "http_serde::request_parts")]`
+///
+/// Serialized as `{"method", "uri", "version", "headers"}`, using the same formats as the modules for these types.
+/// Extensions can't be serialized, so they're skipped, and deserialized `Parts` have no extensions.
+pub mod request_parts {
+    use http::request::Parts;
+    use http::{HeaderMap, Method, Request, Uri, Version};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    #[serde(rename = "Parts")]
+    struct PartsSer<'a> {
+        #[serde(with = "crate::method")]
+        method: &'a Method,
+        #[serde(with = "crate::uri")]
+        uri: &'a Uri,
+        #[serde(with = "crate::version")]
+        version: Version,
+        #[serde(with = "crate::header_map")]
+        headers: &'a HeaderMap,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Parts")]
+    struct PartsDe {
+        #[serde(with = "crate::method")]
+        method: Method,
+        #[serde(with = "crate::uri")]
+        uri: Uri,
+        #[serde(with = "crate::version")]
+        version: Version,
+        #[serde(with = "crate::header_map")]
+        headers: HeaderMap,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(parts: &Parts, ser: S) -> Result<S::Ok, S::Error> {
+        PartsSer {
+            method: &parts.method,
+            uri: &parts.uri,
+            version: parts.version,
+            headers: &parts.headers,
+        }
+        .serialize(ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Parts, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let p = PartsDe::deserialize(de)?;
+        let mut req = Request::builder()
+            .method(p.method)
+            .uri(p.uri)
+            .version(p.version)
+            .body(())
+            .map_err(de::Error::custom)?;
+        *req.headers_mut() = p.headers;
+        Ok(req.into_parts().0)
+    }
+}
+
+/// For `http::response::Parts`
+///
+/// `#[serde(with = "http_serde::response_parts")]`
+///
+/// Serialized as `{"status", "version", "headers"}`, using the same formats as the modules for these types.
+/// Extensions can't be serialized, so they're skipped, and deserialized `Parts` have no extensions.
+pub mod response_parts {
+    use http::response::Parts;
+    use http::{HeaderMap, Response, StatusCode, Version};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    #[serde(rename = "Parts")]
+    struct PartsSer<'a> {
+        #[serde(with = "crate::status_code")]
+        status: StatusCode,
+        #[serde(with = "crate::version")]
+        version: Version,
+        #[serde(with = "crate::header_map")]
+        headers: &'a HeaderMap,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Parts")]
+    struct PartsDe {
+        #[serde(with = "crate::status_code")]
+        status: StatusCode,
+        #[serde(with = "crate::version")]
+        version: Version,
+        #[serde(with = "crate::header_map")]
+        headers: HeaderMap,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(parts: &Parts, ser: S) -> Result<S::Ok, S::Error> {
+        PartsSer {
+            status: parts.status,
+            version: parts.version,
+            headers: &parts.headers,
+        }
+        .serialize(ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Parts, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let p = PartsDe::deserialize(de)?;
+        let mut res = Response::builder()
+            .status(p.status)
+            .version(p.version)
+            .body(())
+            .map_err(de::Error::custom)?;
+        *res.headers_mut() = p.headers;
+        Ok(res.into_parts().0)
+    }
+}
+
+/// For `http::Uri` with the values of sensitive query parameters replaced by `REDACTED`
+///
+/// The order of parameters and the rest of the URI are kept as-is. Parameter names are matched exactly.
+/// Redaction is applied when deserializing too, so the original values are never read back.
+/// The list is a runtime value, so call this from your own `serialize_with` function:
+///
+/// ```rust
+/// # use http::Uri;
+/// fn redacted<S: serde::Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+///     http_serde::uri_redact_params(&["token", "api_key"]).serialize(uri, ser)
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Logged {
+///     #[serde(serialize_with = "redacted", deserialize_with = "http_serde::uri::deserialize")]
+///     uri: Uri,
+/// }
+/// ```
+pub fn uri_redact_params<'a>(names: &'a [&'a str]) -> RedactedParams<'a> {
+    RedactedParams { names }
+}
+
+/// Created by [`uri_redact_params`]
+#[derive(Debug, Clone, Copy)]
+pub struct RedactedParams<'a> {
+    names: &'a [&'a str],
+}
+
+impl RedactedParams<'_> {
+    /// What the values are replaced with
+    pub const REDACTED: &'static str = "REDACTED";
+
+    /// The URI with the values replaced, or `None` if it has nothing to redact
+    fn redact(&self, uri: &http::Uri) -> Option<String> {
+        let query = uri.query()?;
+        let mut changed = false;
+        let params: Vec<_> = query
+            .split('&')
+            .map(|param| {
+                let mut pair = param.splitn(2, '=');
+                match (pair.next(), pair.next()) {
+                    (Some(name), Some(value))
+                        if value != Self::REDACTED && self.names.contains(&name) =>
+                    {
+                        changed = true;
+                        alloc::borrow::Cow::Owned(format!("{}={}", name, Self::REDACTED))
+                    }
+                    _ => alloc::borrow::Cow::Borrowed(param),
+                }
+            })
+            .collect();
+        if !changed {
+            return None;
+        }
+        let full = uri.to_string();
+        let before_query = &full[..full.len() - query.len() - 1];
+        Some(format!("{}?{}", before_query, params.join("&")))
+    }
+
+    /// Writes the URI with the values replaced
+    pub fn serialize<S: serde::Serializer>(
+        &self,
+        uri: &http::Uri,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self.redact(uri) {
+            Some(redacted) => ser.serialize_str(&redacted),
+            None => uri::serialize(uri, ser),
+        }
+    }
+
+    /// Reads the URI, replacing the values if they weren't already
+    pub fn deserialize<'de, D>(&self, de: D) -> Result<http::Uri, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let uri = uri::deserialize(de)?;
+        match self.redact(&uri) {
+            Some(redacted) => redacted.parse().map_err(serde::de::Error::custom),
+            None => Ok(uri),
+        }
+    }
+}
+
+/// For `http::HeaderMap`, dropping repeated identical values of a header
+///
+/// `#[serde(with = "http_serde::header_map_dedup_values")]`
+///
+/// When deserializing, a value is kept only the first time it appears for its header name. Distinct values of the
+/// same name are all kept, in their original order. Values are compared byte-for-byte, so `a` and `A` are distinct.
+/// Serialization is the same as `http_serde::header_map`.
+pub mod header_map_dedup_values {
+    use http::HeaderMap;
+    use serde::{Deserializer, Serializer};
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        crate::header_map::serialize(headers, ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let headers = crate::header_map::deserialize(de)?;
+        let mut deduped = HeaderMap::with_capacity(headers.keys_len());
+        for (name, value) in &headers {
+            if !deduped.get_all(name).iter().any(|v| v == value) {
+                deduped.append(name, value.clone());
+            }
+        }
+        Ok(deduped)
+    }
+}
+
+/// For `http::Uri`, with the file extension of its path
+///
+/// `#[serde(with = "http_serde::uri_with_ext")]`
+///
+/// Serialized as `{"uri": "https://x/a/b.json?q=1", "ext": "json"}`. The extension is taken from the last segment
+/// of the path, so the query doesn't affect it. It's `null` when the last segment has no extension, including paths
+/// ending with `/` and dotfiles like `/.env`. The extension is ignored when deserializing.
+pub mod uri_with_ext {
+    use alloc::string::String;
+    use http::Uri;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// The extension (without the dot) of the last path segment, if there is one
+    pub fn ext(uri: &Uri) -> Option<&str> {
+        let segment = uri.path().rsplit('/').next()?;
+        match segment.rfind('.') {
+            Some(0) | None => None,
+            Some(dot) if dot + 1 == segment.len() => None,
+            Some(dot) => Some(&segment[dot + 1..]),
+        }
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename = "Uri")]
+    struct UriSer<'a> {
+        #[serde(with = "crate::uri")]
+        uri: &'a Uri,
+        ext: Option<&'a str>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Uri")]
+    struct UriDe {
+        #[serde(with = "crate::uri")]
+        uri: Uri,
+        #[serde(default, rename = "ext")]
+        _ext: Option<String>,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        UriSer { uri, ext: ext(uri) }.serialize(ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        UriDe::deserialize(de).map(|u| u.uri)
+    }
+}
+
+/// For `http::StatusCode`, with a flag telling whether the response is cacheable by default
+///
+/// `#[serde(with = "http_serde::status_code_cacheable")]`
+///
+/// Serialized as `{"code": 200, "cacheable": true}`. The flag is derived from the code, so it's ignored when deserializing.
+///
+/// Codes in [`DEFAULT_CACHEABLE`](status_code_cacheable::DEFAULT_CACHEABLE) are cacheable. To use a different set,
+/// call [`serialize_with`](status_code_cacheable::serialize_with) from your own `serialize_with` function.
+pub mod status_code_cacheable {
+    use http::StatusCode;
+    use serde::de::Deserialize;
+    use serde::ser::SerializeStruct;
+    use serde::{Deserializer, Serializer};
+
+    /// The codes that are heuristically cacheable by default (RFC 9110 section 15.1)
+    pub const DEFAULT_CACHEABLE: &[u16] = &[200, 203, 204, 206, 300, 301, 404, 405, 410, 414, 501];
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        serialize_with(status, DEFAULT_CACHEABLE, ser)
+    }
+
+    /// Like `serialize`, but with a custom set of cacheable codes
+    pub fn serialize_with<S: Serializer>(
+        status: &StatusCode,
+        cacheable: &[u16],
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut s = ser.serialize_struct("StatusCode", 2)?;
+        s.serialize_field("code", &status.as_u16())?;
+        s.serialize_field("cacheable", &cacheable.contains(&status.as_u16()))?;
+        s.end()
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "StatusCode")]
+    struct Repr {
+        #[serde(with = "crate::status_code")]
+        code: StatusCode,
+        #[serde(default, rename = "cacheable")]
+        _cacheable: bool,
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Repr::deserialize(de).map(|r| r.code)
+    }
+}
+
+/// For `http::HeaderMap`, as the string of headers covered by a signature (in the style of RFC 9421 HTTP Message Signatures)
+///
+/// Only the listed headers are serialized, as `name: value` lines in the order of the list, joined with `\n`
+/// (no trailing newline). Names are lowercase, values have surrounding whitespace removed, and multiple values
+/// of a header are joined with `, `. Serialization fails if a listed header is missing or isn't valid UTF-8.
+///
+/// Deserialization parses such a string back into a map with just these headers, and fails if the string has
+/// a header that isn't listed or lacks one that is. The list is a runtime value, so call this from your own functions:
+///
+/// ```rust
+/// # use http::HeaderMap;
+/// fn signed<S: serde::Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+///     http_serde::header_map_signing(&["host", "date"]).serialize(headers, ser)
+/// }
+///
+/// fn read_signed<'de, D: serde::Deserializer<'de>>(de: D) -> Result<HeaderMap, D::Error> {
+///     http_serde::header_map_signing(&["host", "date"]).deserialize(de)
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Signed {
+///     #[serde(serialize_with = "signed", deserialize_with = "read_signed")]
+///     covered: HeaderMap,
+/// }
+/// ```
+pub fn header_map_signing<'a>(names: &'a [&'a str]) -> SigningHeaders<'a> {
+    SigningHeaders { names }
+}
+
+/// Created by [`header_map_signing`]
+#[derive(Debug, Clone, Copy)]
+pub struct SigningHeaders<'a> {
+    names: &'a [&'a str],
+}
+
+impl SigningHeaders<'_> {
+    /// The signing string for these headers
+    pub fn to_signing_string(&self, headers: &http::HeaderMap) -> Result<String, String> {
+        let mut lines = Vec::with_capacity(self.names.len());
+        for name in self.names {
+            let name = name.to_ascii_lowercase();
+            let mut values = Vec::new();
+            for val in headers.get_all(name.as_str()) {
+                let val = val
+                    .to_str()
+                    .map_err(|_| format!("value of header `{}` isn't valid UTF-8", name))?;
+                values.push(val.trim());
+            }
+            if values.is_empty() {
+                return Err(format!("missing signed header `{}`", name));
+            }
+            lines.push(format!("{}: {}", name, values.join(", ")));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Parses a signing string for these headers
+    pub fn from_signing_string(&self, signing: &str) -> Result<http::HeaderMap, String> {
+        let headers = header_map_raw_block::from_bytes(signing.as_bytes())?;
+        if let Some(name) = headers.keys().find(|k| {
+            !self
+                .names
+                .iter()
+                .any(|n| n.eq_ignore_ascii_case(k.as_str()))
+        }) {
+            return Err(format!("header `{}` isn't signed", name));
+        }
+        if let Some(name) = self.names.iter().find(|&&n| !headers.contains_key(n)) {
+            return Err(format!("missing signed header `{}`", name));
+        }
+        Ok(headers)
+    }
+
+    /// Writes the signing string
+    pub fn serialize<S: serde::Serializer>(
+        &self,
+        headers: &http::HeaderMap,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        let signing = self
+            .to_signing_string(headers)
+            .map_err(serde::ser::Error::custom)?;
+        ser.serialize_str(&signing)
+    }
+
+    /// Reads the signing string
+    pub fn deserialize<'de, D>(&self, de: D) -> Result<http::HeaderMap, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let signing = <String as serde::Deserialize>::deserialize(de)?;
+        self.from_signing_string(&signing)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// For `http::Method`, limited to the standard and WebDAV methods
+///
+/// `#[serde(with = "http_serde::method_webdav")]`
+///
+/// Deserialization accepts only the methods in [`METHODS`](method_webdav::METHODS): the ones from RFC 9110, `PATCH`,
+/// and `PROPFIND`, `PROPPATCH`, `MKCOL`, `COPY`, `MOVE`, `LOCK`, `UNLOCK` from WebDAV (RFC 4918).
+/// Other methods are an error. Names are case-sensitive. Serialization is the same as `http_serde::method`.
+pub mod method_webdav {
+    use alloc::borrow::Cow;
+    use http::Method;
+    use serde::de::{Deserialize, Unexpected};
+    use serde::{de, Deserializer, Serializer};
+
+    /// All accepted methods
+    pub const METHODS: &[&str] = &[
+        "GET",
+        "HEAD",
+        "POST",
+        "PUT",
+        "DELETE",
+        "CONNECT",
+        "OPTIONS",
+        "TRACE",
+        "PATCH",
+        "PROPFIND",
+        "PROPPATCH",
+        "MKCOL",
+        "COPY",
+        "MOVE",
+        "LOCK",
+        "UNLOCK",
+    ];
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(method: &Method, ser: S) -> Result<S::Ok, S::Error> {
+        crate::method::serialize(method, ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Method, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = Cow::<str>::deserialize(de)?;
+        if !METHODS.contains(&&*name) {
+            return Err(de::Error::invalid_value(
+                Unexpected::Str(&name),
+                &"standard or WebDAV method",
+            ));
+        }
+        name.parse().map_err(de::Error::custom)
+    }
+}
+
+/// For `http::Uri`, split into authority and path with query
+///
+/// `#[serde(with = "http_serde::uri_authority_pq")]`
+///
+/// `https://x/a?b=c` is serialized as `{"scheme": "https", "authority": "x", "path_and_query": "/a?b=c"}`.
+/// The scheme is kept in its own field, so that the URI round-trips. Parts that the URI doesn't have are `null`,
+/// e.g. `authority` and `scheme` of a relative `/a?b=c`, or `path_and_query` of an authority-form `x:443`.
+/// Deserialization rebuilds the URI from `http::uri::Parts`, and fails if they don't form a valid URI.
+pub mod uri_authority_pq {
+    use http::uri::{Authority, Parts, PathAndQuery, Scheme};
+    use http::Uri;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    #[serde(rename = "Uri")]
+    struct UriSer<'a> {
+        #[serde(with = "crate::option::scheme")]
+        scheme: &'a Option<Scheme>,
+        #[serde(with = "crate::option::authority")]
+        authority: &'a Option<Authority>,
+        #[serde(with = "crate::option::path_and_query")]
+        path_and_query: &'a Option<PathAndQuery>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Uri")]
+    struct UriDe {
+        #[serde(with = "crate::option::scheme", default)]
+        scheme: Option<Scheme>,
+        #[serde(with = "crate::option::authority", default)]
+        authority: Option<Authority>,
+        #[serde(with = "crate::option::path_and_query", default)]
+        path_and_query: Option<PathAndQuery>,
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        let parts = Parts::from(uri.clone());
+        UriSer {
+            scheme: &parts.scheme,
+            authority: &parts.authority,
+            path_and_query: &parts.path_and_query,
+        }
+        .serialize(ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let u = UriDe::deserialize(de)?;
+        let mut parts = Parts::default();
+        parts.scheme = u.scheme;
+        parts.authority = u.authority;
+        parts.path_and_query = u.path_and_query;
+        Uri::from_parts(parts).map_err(de::Error::custom)
+    }
+}
+
+/// For `http::HeaderMap`, with all values hex-encoded
+///
+/// `#[serde(with = "http_serde::header_map_hex")]`
+///
+/// Every value is written as lowercase hex of its bytes, so the output is plain ASCII and any value round-trips:
+/// `{"x-bin": "00ff", "x-multi": ["61", "62"]}`. Names are lowercase, as in `http_serde::header_map`.
+/// Like there, a single value is a string and multiple values are an array in human-readable formats.
+/// Binary formats always use an array. Uppercase hex is accepted when deserializing.
+pub mod header_map_hex {
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::fmt;
+    use core::fmt::Write;
+    use http::header::{GetAll, HeaderName};
+    use http::{HeaderMap, HeaderValue};
+    use serde::de::{MapAccess, Unexpected, Visitor};
+    use serde::ser::SerializeSeq;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    fn encode(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(bytes.len() * 2);
+        for b in bytes {
+            let _ = write!(out, "{:02x}", b);
+        }
+        out
+    }
+
+    fn decode(hex: &str) -> Option<Vec<u8>> {
+        let pairs = hex.as_bytes().chunks_exact(2);
+        if !pairs.remainder().is_empty() {
+            return None;
+        }
+        pairs
+            .map(|pair| {
+                // `from_str_radix` alone would accept a sign, like `+a`
+                if !pair.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok()
+            })
+            .collect()
+    }
+
+    struct Values<'a>(GetAll<'a, HeaderValue>);
+
+    impl Serialize for Values<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            let mut values = self.0.iter();
+            match (values.next(), values.next()) {
+                (Some(one), None) if ser.is_human_readable() => {
+                    ser.serialize_str(&encode(one.as_bytes()))
+                }
+                _ => {
+                    let mut seq = ser.serialize_seq(Some(self.0.iter().count()))?;
+                    for v in self.0.iter() {
+                        seq.serialize_element(&encode(v.as_bytes()))?;
+                    }
+                    seq.end()
+                }
+            }
+        }
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_map(
+            headers
+                .keys()
+                .map(|k| (k.as_str(), Values(headers.get_all(k)))),
+        )
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMore {
+        One(String),
+        More(Vec<String>),
+    }
+
+    struct HexVisitor {
+        is_human_readable: bool,
+    }
+
+    impl<'de> Visitor<'de> for HexVisitor {
+        type Value = HeaderMap;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("map of header names to hex-encoded values")
+        }
+
+        fn visit_map<M: MapAccess<'de>>(self, mut access: M) -> Result<HeaderMap, M::Error> {
+            let mut map = HeaderMap::with_capacity(access.size_hint().unwrap_or(0));
+            while let Some(key) = access.next_key::<String>()? {
+                let key = HeaderName::from_bytes(key.as_bytes())
+                    .map_err(|_| de::Error::invalid_value(Unexpected::Str(&key), &self))?;
+                let values = if self.is_human_readable {
+                    match access.next_value()? {
+                        OneOrMore::One(one) => vec![one],
+                        OneOrMore::More(more) => more,
+                    }
+                } else {
+                    access.next_value::<Vec<String>>()?
+                };
+                for hex in values {
+                    let bytes = decode(&hex).ok_or_else(|| {
+                        de::Error::invalid_value(Unexpected::Str(&hex), &"hex-encoded bytes")
+                    })?;
+                    let val = HeaderValue::from_bytes(&bytes).map_err(|_| {
+                        de::Error::invalid_value(Unexpected::Bytes(&bytes), &"header value")
+                    })?;
+                    map.append(&key, val);
+                }
+            }
+            Ok(map)
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let is_human_readable = de.is_human_readable();
+        de.deserialize_map(HexVisitor { is_human_readable })
+    }
+}
+
+/// For `http::StatusCode`, tagged as success or error
+///
+/// `#[serde(with = "http_serde::status_code_result")]`
+///
+/// 2xx codes are serialized as `{"ok": 200}`, and all others as `{"err": 500}`. Deserialization checks that the tag
+/// matches the code, so `{"ok": 500}` is an error.
+pub mod status_code_result {
+    use http::StatusCode;
+    use serde::de::Unexpected;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// `Ok` for 2xx codes, `Err` otherwise
+    pub fn to_result(status: StatusCode) -> Result<StatusCode, StatusCode> {
+        if status.is_success() {
+            Ok(status)
+        } else {
+            Err(status)
+        }
+    }
+
+    /// The code in either case
+    pub fn from_result(result: Result<StatusCode, StatusCode>) -> StatusCode {
+        match result {
+            Ok(status) | Err(status) => status,
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "StatusCode", rename_all = "lowercase")]
+    enum Repr {
+        Ok(#[serde(with = "crate::status_code")] StatusCode),
+        Err(#[serde(with = "crate::status_code")] StatusCode),
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        match to_result(*status) {
+            Ok(status) => Repr::Ok(status),
+            Err(status) => Repr::Err(status),
+        }
+        .serialize(ser)
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<StatusCode, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (status, ok) = match Repr::deserialize(de)? {
+            Repr::Ok(status) => (status, true),
+            Repr::Err(status) => (status, false),
+        };
+        if status.is_success() != ok {
+            let expected = if ok {
+                &"2xx status code"
+            } else {
+                &"non-2xx status code"
+            };
+            return Err(de::Error::invalid_value(
+                Unexpected::Unsigned(status.as_u16().into()),
+                expected,
+            ));
+        }
+        Ok(status)
+    }
+}
+
+/// For `http::Uri` with a limit on the number of path segments
+///
+/// Deserialization fails if the path has more than `max` segments. Empty segments (as in `/a//b` or a trailing `/`)
+/// aren't counted, so `/a/b/c/` has 3 segments. The query isn't part of the path. Serialization is the same as `http_serde::uri`.
+/// The limit is a runtime value, so call this from your own `deserialize_with` function:
+///
+/// ```rust
+/// # use http::Uri;
+/// fn at_most_5<'de, D: serde::Deserializer<'de>>(de: D) -> Result<Uri, D::Error> {
+///     http_serde::uri_max_segments(5).deserialize(de)
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Route {
+///     #[serde(serialize_with = "http_serde::uri::serialize", deserialize_with = "at_most_5")]
+///     uri: Uri,
+/// }
+/// ```
+pub fn uri_max_segments(max: usize) -> MaxSegments {
+    MaxSegments { max }
+}
+
+/// Created by [`uri_max_segments`]
+#[derive(Debug, Clone, Copy)]
+pub struct MaxSegments {
+    max: usize,
+}
+
+impl MaxSegments {
+    /// Same as `http_serde::uri::serialize`
+    pub fn serialize<S: serde::Serializer>(
+        &self,
+        uri: &http::Uri,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        uri::serialize(uri, ser)
+    }
+
+    /// Fails if the path has too many segments
+    pub fn deserialize<'de, D>(&self, de: D) -> Result<http::Uri, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let uri = uri::deserialize(de)?;
+        let segments = uri.path().split('/').filter(|s| !s.is_empty()).count();
+        if segments > self.max {
+            return Err(serde::de::Error::custom(format_args!(
+                "path has {} segments, but at most {} are allowed",
+                segments, self.max
+            )));
+        }
+        Ok(uri)
+    }
+}
+
+/// For `http::HeaderMap`, with `Content-Type` parsed as a `mime::Mime`. Requires the `mime` and `std` features.
+///
+/// `#[serde(with = "http_serde::header_map_mime")]`
+///
+/// In human-readable formats `Content-Type` is serialized as
+/// `{"type": "application", "subtype": "json", "params": {"charset": "utf-8"}}`, and other headers are the same as in
+/// `http_serde::header_map`. When deserializing, the header is reassembled into a valid value (params end up in
+/// alphabetical order). A missing `Content-Type` is fine, and one that can't be parsed, or has multiple values, is kept as-is.
+/// Binary formats use the same representation as `http_serde::header_map`.
+#[cfg(all(feature = "mime", feature = "std"))]
+pub mod header_map_mime {
+    use alloc::collections::BTreeMap;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::fmt;
+    use http::header::{HeaderName, CONTENT_TYPE};
+    use http::{HeaderMap, HeaderValue};
+    use mime::Mime;
+    use serde::de::{MapAccess, Unexpected, Visitor};
+    use serde::ser::SerializeStruct;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    struct Params<'a>(&'a Mime);
+
+    impl Serialize for Params<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            ser.collect_map(self.0.params().map(|(k, v)| (k.as_str(), v.as_str())))
+        }
+    }
+
+    enum Value<'a> {
+        Mime(Mime),
+        Raw(crate::header_map::ToSeq<'a>),
+    }
+
+    impl Serialize for Value<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Value::Mime(mime) => {
+                    let essence = mime.essence_str();
+                    let slash = essence.find('/').unwrap_or(essence.len());
+                    let mut s = ser.serialize_struct("Mime", 3)?;
+                    s.serialize_field("type", &essence[..slash])?;
+                    s.serialize_field("subtype", essence.get(slash + 1..).unwrap_or(""))?;
+                    s.serialize_field("params", &Params(mime))?;
+                    s.end()
+                }
+                Value::Raw(values) => values.serialize(ser),
+            }
+        }
+    }
+
+    fn parse(headers: &HeaderMap) -> Option<Mime> {
+        let mut values = headers.get_all(CONTENT_TYPE).iter();
+        match (values.next(), values.next()) {
+            (Some(one), None) => one.to_str().ok()?.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        if !ser.is_human_readable() {
+            return crate::header_map::serialize(headers, ser);
+        }
+        let mime = parse(headers);
+        ser.collect_map(headers.keys().map(|k| {
+            let value = match &mime {
+                Some(mime) if k == CONTENT_TYPE => Value::Mime(mime.clone()),
+                _ => Value::Raw(crate::header_map::ToSeq(headers.get_all(k))),
+            };
+            (k.as_str(), value)
+        }))
+    }
+
+    #[derive(Deserialize)]
+    struct RawValue(#[serde(with = "crate::header_value")] HeaderValue);
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawValues {
+        One(RawValue),
+        More(Vec<RawValue>),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ContentType {
+        Mime {
+            #[serde(rename = "type")]
+            type_: String,
+            subtype: String,
+            #[serde(default)]
+            params: BTreeMap<String, String>,
+        },
+        Raw(RawValues),
+    }
+
+    fn is_token(val: &str) -> bool {
+        !val.is_empty()
+            && val
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+    }
+
+    fn assemble<E: de::Error>(
+        type_: &str,
+        subtype: &str,
+        params: &BTreeMap<String, String>,
+    ) -> Result<HeaderValue, E> {
+        let mut value = format!("{}/{}", type_, subtype);
+        for (k, v) in params {
+            if is_token(v) {
+                value += &format!("; {}={}", k, v);
+            } else {
+                value += &format!(
+                    "; {}=\"{}\"",
+                    k,
+                    v.replace('\\', "\\\\").replace('"', "\\\"")
+                );
+            }
+        }
+        let mime: Mime = value
+            .parse()
+            .map_err(|_| de::Error::invalid_value(Unexpected::Str(&value), &"valid media type"))?;
+        HeaderValue::from_str(mime.as_ref())
+            .map_err(|_| de::Error::invalid_value(Unexpected::Str(&value), &"valid header value"))
+    }
+
+    struct MimeVisitor;
+
+    impl<'de> Visitor<'de> for MimeVisitor {
+        type Value = HeaderMap;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("map of header names to values")
+        }
+
+        fn visit_map<M: MapAccess<'de>>(self, mut access: M) -> Result<HeaderMap, M::Error> {
+            let mut map = HeaderMap::with_capacity(access.size_hint().unwrap_or(0));
+            while let Some(key) = access.next_key::<String>()? {
+                let key = HeaderName::from_bytes(key.as_bytes())
+                    .map_err(|_| de::Error::invalid_value(Unexpected::Str(&key), &self))?;
+                let raw = if key == CONTENT_TYPE {
+                    match access.next_value()? {
+                        ContentType::Mime {
+                            type_,
+                            subtype,
+                            params,
+                        } => {
+                            map.append(&key, assemble(&type_, &subtype, &params)?);
+                            continue;
+                        }
+                        ContentType::Raw(raw) => raw,
+                    }
+                } else {
+                    access.next_value()?
+                };
+                match raw {
+                    RawValues::One(RawValue(val)) => {
+                        map.append(&key, val);
+                    }
+                    RawValues::More(vals) => {
+                        for RawValue(val) in vals {
+                            map.append(&key, val);
+                        }
+                    }
+                }
+            }
+            Ok(map)
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<HeaderMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !de.is_human_readable() {
+            return crate::header_map::deserialize(de);
+        }
+        de.deserialize_map(MimeVisitor)
+    }
+}
+
+/// For `http::Version`, with HTTP/3 labeled by its transport, for display
+///
+/// `#[serde(with = "http_serde::version_transport")]`
+///
+/// Meant for logs and other output read by people: in human-readable formats HTTP/3 is serialized as `"h3 (QUIC)"`,
+/// and other versions are the same as in `http_serde::version`. Deserialization strips the ` (QUIC)` annotation,
+/// and also accepts the plain forms (`"h3"`, `"HTTP/3.0"`). Binary formats are the same as `http_serde::version`.
+pub mod version_transport {
+    use alloc::borrow::Cow;
+    use http::Version;
+    use serde::de::IntoDeserializer;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// How HTTP/3 is serialized
+    pub const HTTP_3_QUIC: &str = "h3 (QUIC)";
+
+    /// Implementation detail. Use derive annotations instead.
+    pub fn serialize<S: Serializer>(version: &Version, ser: S) -> Result<S::Ok, S::Error> {
+        if ser.is_human_readable() && *version == Version::HTTP_3 {
+            ser.serialize_str(HTTP_3_QUIC)
+        } else {
+            crate::version::serialize(version, ser)
+        }
+    }
+
+    /// Implementation detail.
+    pub fn deserialize<'de, D>(de: D) -> Result<Version, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !de.is_human_readable() {
+            return crate::version::deserialize(de);
+        }
+        let val = Cow::<str>::deserialize(de)?;
+        const QUIC: &str = " (QUIC)";
+        let plain = if val.ends_with(QUIC) {
+            &val[..val.len() - QUIC.len()]
+        } else {
+            &val
+        };
+        if plain == "h3" {
+            return Ok(Version::HTTP_3);
+        }
+        crate::version::deserialize(plain.into_deserializer())
     }
 }
@@ -0,0 +1,153 @@
+//! For `Option`s of the types supported by this crate
+//!
+//! `#[serde(with = "http_serde::option::<appropriate method>")]`
+//!
+//! ```rust
+//! # use serde::*;
+//! # use http::*;
+//! #[derive(Serialize, Deserialize)]
+//! struct MyStruct {
+//!     #[serde(with = "http_serde::option::status_code", default)]
+//!     status: Option<StatusCode>,
+//! }
+//! ```
+
+macro_rules! option_of {
+    ($(#[$meta:meta])* $name:ident, $($module:ident)::+, $ty:ty) => {
+        $(#[$meta])*
+        pub mod $name {
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+            struct Ref<'a>(&'a $ty);
+
+            impl Serialize for Ref<'_> {
+                fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                    crate::$($module)::+::serialize(self.0, ser)
+                }
+            }
+
+            struct Owned($ty);
+
+            impl<'de> Deserialize<'de> for Owned {
+                fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                    crate::$($module)::+::deserialize(de).map(Owned)
+                }
+            }
+
+            /// Implementation detail. Use derive annotations instead.
+            pub fn serialize<S: Serializer>(val: &Option<$ty>, ser: S) -> Result<S::Ok, S::Error> {
+                match val {
+                    Some(val) => ser.serialize_some(&Ref(val)),
+                    None => ser.serialize_none(),
+                }
+            }
+
+            /// Implementation detail.
+            pub fn deserialize<'de, D>(de: D) -> Result<Option<$ty>, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Ok(Option::<Owned>::deserialize(de)?.map(|Owned(val)| val))
+            }
+        }
+    };
+}
+
+option_of!(
+    /// For `Option<http::HeaderMap>`
+    ///
+    /// `#[serde(with = "http_serde::option::header_map")]`
+    header_map,
+    header_map,
+    http::HeaderMap
+);
+
+option_of!(
+    /// For `Option<http::HeaderMap>`, as a list of `[name, value]` pairs
+    ///
+    /// `#[serde(with = "http_serde::option::header_map_ordered")]`
+    header_map_ordered,
+    header_map::ordered,
+    http::HeaderMap
+);
+
+option_of!(
+    /// For `Option<http::StatusCode>`
+    ///
+    /// `#[serde(with = "http_serde::option::status_code")]`
+    status_code,
+    status_code,
+    http::StatusCode
+);
+
+option_of!(
+    /// For `Option<http::Method>`
+    ///
+    /// `#[serde(with = "http_serde::option::method")]`
+    method,
+    method,
+    http::Method
+);
+
+option_of!(
+    /// For `Option<http::Uri>`
+    ///
+    /// `#[serde(with = "http_serde::option::uri")]`
+    uri,
+    uri,
+    http::Uri
+);
+
+option_of!(
+    /// For `Option<http::uri::Authority>`
+    ///
+    /// `#[serde(with = "http_serde::option::authority")]`
+    authority,
+    authority,
+    http::uri::Authority
+);
+
+option_of!(
+    /// For `Option<http::Version>`
+    ///
+    /// `#[serde(with = "http_serde::option::version")]`
+    version,
+    version,
+    http::Version
+);
+
+option_of!(
+    /// For `Option<http::header::HeaderName>`
+    ///
+    /// `#[serde(with = "http_serde::option::header_name")]`
+    header_name,
+    header_name,
+    http::header::HeaderName
+);
+
+option_of!(
+    /// For `Option<http::HeaderValue>`
+    ///
+    /// `#[serde(with = "http_serde::option::header_value")]`
+    header_value,
+    header_value,
+    http::HeaderValue
+);
+
+option_of!(
+    /// For `Option<http::uri::Scheme>`
+    ///
+    /// `#[serde(with = "http_serde::option::scheme")]`
+    scheme,
+    scheme,
+    http::uri::Scheme
+);
+
+option_of!(
+    /// For `Option<http::uri::PathAndQuery>`
+    ///
+    /// `#[serde(with = "http_serde::option::path_and_query")]`
+    path_and_query,
+    path_and_query,
+    http::uri::PathAndQuery
+);
@@ -0,0 +1,52 @@
+use core::marker::PhantomData;
+use serde::{Deserializer, Serializer};
+use serde_with::{DeserializeAs, SerializeAs};
+
+/// Adapter for `serde_with`'s `#[serde_as]`, for all the types supported by this crate
+///
+/// `As<T>` behaves the same as `#[serde(with = "http_serde::<module for T>")]`, but can be nested in other types:
+///
+/// ```rust
+/// # use http::{StatusCode, Uri};
+/// # use std::collections::HashMap;
+/// use http_serde::As;
+///
+/// #[serde_with::serde_as]
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Links {
+///     #[serde_as(as = "Vec<As<Uri>>")]
+///     uris: Vec<Uri>,
+///     #[serde_as(as = "HashMap<_, As<StatusCode>>")]
+///     statuses: HashMap<String, StatusCode>,
+/// }
+/// ```
+///
+/// Requires the `serde_with` feature.
+pub struct As<T>(PhantomData<T>);
+
+macro_rules! serde_as {
+    ($module:ident, $ty:ty) => {
+        impl SerializeAs<$ty> for As<$ty> {
+            fn serialize_as<S: Serializer>(source: &$ty, ser: S) -> Result<S::Ok, S::Error> {
+                crate::$module::serialize(source, ser)
+            }
+        }
+
+        impl<'de> DeserializeAs<'de, $ty> for As<$ty> {
+            fn deserialize_as<D: Deserializer<'de>>(de: D) -> Result<$ty, D::Error> {
+                crate::$module::deserialize(de)
+            }
+        }
+    };
+}
+
+serde_as!(header_map, http::HeaderMap);
+serde_as!(status_code, http::StatusCode);
+serde_as!(method, http::Method);
+serde_as!(uri, http::Uri);
+serde_as!(authority, http::uri::Authority);
+serde_as!(version, http::Version);
+serde_as!(header_name, http::header::HeaderName);
+serde_as!(header_value, http::HeaderValue);
+serde_as!(scheme, http::uri::Scheme);
+serde_as!(path_and_query, http::uri::PathAndQuery);
@@ -0,0 +1,144 @@
+//! Newtypes that implement `Serialize` and `Deserialize` directly
+//!
+//! `#[serde(with)]` applies only to the annotated field, so it can't reach types nested in containers like `Vec` or
+//! `HashMap`. These wrappers can be used anywhere instead. They serialize exactly like the module of the same name.
+//!
+//! ```rust
+//! # use serde::*;
+//! # use std::collections::HashMap;
+//! use http_serde::wrap;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct MyStruct {
+//!     redirects: Vec<wrap::Uri>,
+//!     methods: HashMap<String, wrap::Method>,
+//! }
+//! ```
+
+use core::ops::{Deref, DerefMut};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! wrap {
+    ($(#[$meta:meta])* $name:ident, $module:ident, $ty:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name(pub $ty);
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                crate::$module::serialize(&self.0, ser)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                crate::$module::deserialize(de).map($name)
+            }
+        }
+
+        impl From<$ty> for $name {
+            fn from(val: $ty) -> Self {
+                $name(val)
+            }
+        }
+
+        impl From<$name> for $ty {
+            fn from(val: $name) -> Self {
+                val.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = $ty;
+
+            fn deref(&self) -> &$ty {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut $ty {
+                &mut self.0
+            }
+        }
+    };
+}
+
+wrap!(
+    /// `http::HeaderMap` serialized like `http_serde::header_map`
+    #[derive(Default)]
+    HeaderMap,
+    header_map,
+    http::HeaderMap
+);
+
+wrap!(
+    /// `http::StatusCode` serialized like `http_serde::status_code`
+    #[derive(Copy, Hash, PartialOrd, Ord, Default)]
+    StatusCode,
+    status_code,
+    http::StatusCode
+);
+
+wrap!(
+    /// `http::Method` serialized like `http_serde::method`
+    #[derive(Hash, Default)]
+    Method,
+    method,
+    http::Method
+);
+
+wrap!(
+    /// `http::Uri` serialized like `http_serde::uri`
+    #[derive(Hash, Default)]
+    Uri,
+    uri,
+    http::Uri
+);
+
+wrap!(
+    /// `http::uri::Authority` serialized like `http_serde::authority`
+    #[derive(Hash)]
+    Authority,
+    authority,
+    http::uri::Authority
+);
+
+wrap!(
+    /// `http::Version` serialized like `http_serde::version`
+    #[derive(Copy, Hash, PartialOrd, Ord, Default)]
+    Version,
+    version,
+    http::Version
+);
+
+wrap!(
+    /// `http::header::HeaderName` serialized like `http_serde::header_name`
+    #[derive(Hash)]
+    HeaderName,
+    header_name,
+    http::header::HeaderName
+);
+
+wrap!(
+    /// `http::HeaderValue` serialized like `http_serde::header_value`
+    #[derive(Hash, PartialOrd, Ord)]
+    HeaderValue,
+    header_value,
+    http::HeaderValue
+);
+
+wrap!(
+    /// `http::uri::Scheme` serialized like `http_serde::scheme`
+    #[derive(Hash)]
+    Scheme,
+    scheme,
+    http::uri::Scheme
+);
+
+wrap!(
+    /// `http::uri::PathAndQuery` serialized like `http_serde::path_and_query`
+    PathAndQuery,
+    path_and_query,
+    http::uri::PathAndQuery
+);
@@ -76,3 +76,2068 @@ fn roundtrip() {
         assert_eq!(format!("{:?}", back.5), "HTTP/2.0");
     }
 }
+
+#[test]
+fn common_status() {
+    use http::StatusCode;
+    use http_serde::CommonStatus;
+    use std::collections::HashSet;
+    use std::convert::TryFrom;
+
+    assert_eq!("200", serde_json::to_string(&CommonStatus::Ok).unwrap());
+    assert_eq!(CommonStatus::Ok, serde_json::from_str("200").unwrap());
+    assert_eq!(
+        "418",
+        serde_json::to_string(&CommonStatus::Other(418)).unwrap()
+    );
+    assert_eq!(
+        CommonStatus::Other(418),
+        serde_json::from_str("418").unwrap()
+    );
+    assert!(serde_json::from_str::<CommonStatus>("1000").is_err());
+
+    assert_eq!(
+        CommonStatus::NotFound,
+        CommonStatus::from(StatusCode::NOT_FOUND)
+    );
+    assert_eq!(CommonStatus::Other(418), StatusCode::IM_A_TEAPOT.into());
+    assert_eq!(
+        StatusCode::OK,
+        StatusCode::try_from(CommonStatus::Ok).unwrap()
+    );
+    assert_eq!(
+        StatusCode::IM_A_TEAPOT,
+        StatusCode::try_from(CommonStatus::Other(418)).unwrap()
+    );
+    assert!(StatusCode::try_from(CommonStatus::Other(1000)).is_err());
+
+    // Compared by the numeric code, whichever variant holds it
+    assert_eq!(CommonStatus::Ok, CommonStatus::Other(200));
+    assert_ne!(CommonStatus::Ok, CommonStatus::Other(201));
+    let set: HashSet<_> = vec![CommonStatus::Ok, CommonStatus::Other(200)]
+        .into_iter()
+        .collect();
+    assert_eq!(1, set.len());
+}
+
+#[test]
+fn header_map_end_to_end() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_end_to_end")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert("content-type", HeaderValue::from_static("text/plain"));
+    map.insert(
+        "connection",
+        HeaderValue::from_static("keep-alive, X-Secret"),
+    );
+    map.insert("keep-alive", HeaderValue::from_static("timeout=5"));
+    map.insert("transfer-encoding", HeaderValue::from_static("chunked"));
+    map.insert("x-secret", HeaderValue::from_static("hunter2"));
+
+    let json = serde_json::to_string(&Wrap(map)).unwrap();
+    assert_eq!("{\"content-type\":\"text/plain\"}", json);
+
+    let back: Wrap = serde_json::from_str(
+        "{\"content-type\":\"text/plain\",\"connection\":\"x-other\",\"x-other\":\"1\",\"upgrade\":\"h2c\"}",
+    )
+    .unwrap();
+    assert_eq!(1, back.0.len());
+    assert_eq!("text/plain", back.0["content-type"]);
+}
+
+#[test]
+fn uri_raw() {
+    use http_serde::uri_raw::RawUri;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_raw")] RawUri);
+
+    let json = "\"HTTP://Example.COM?q=%7e%7E+%2f\"";
+    let back: Wrap = serde_json::from_str(json).unwrap();
+    assert_eq!("/", back.0.uri().path());
+    assert_eq!(Some("q=%7e%7E+%2f"), back.0.uri().query());
+    assert_ne!("HTTP://Example.COM?q=%7e%7E+%2f", back.0.uri().to_string());
+    assert_eq!(json, serde_json::to_string(&back).unwrap());
+
+    assert!(serde_json::from_str::<Wrap>("\"exa mple\"").is_err());
+}
+
+#[cfg(all(feature = "cookie", feature = "std"))]
+#[test]
+fn cookie_jar() {
+    use cookie::{Cookie, CookieJar};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::cookie_jar")] CookieJar);
+
+    let mut jar = CookieJar::new();
+    jar.add(Cookie::new("session", "abc123"));
+    jar.add(Cookie::build(("theme", "dark")).path("/app"));
+
+    let json = serde_json::to_string(&Wrap(jar)).unwrap();
+    assert_eq!(
+        "{\"set-cookie\":[\"session=abc123\",\"theme=dark; Path=/app\"]}",
+        json
+    );
+
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(2, back.0.iter().count());
+    assert_eq!("abc123", back.0.get("session").unwrap().value());
+    assert_eq!(Some("/app"), back.0.get("theme").unwrap().path());
+    assert_eq!(0, back.0.delta().count());
+
+    let bin = bincode::serialize(&back).unwrap();
+    let back: Wrap = bincode::deserialize(&bin).unwrap();
+    assert_eq!("dark", back.0.get("theme").unwrap().value());
+
+    let back: Wrap = serde_json::from_str("{\"cookie\":\"a=1; b=2\"}").unwrap();
+    assert_eq!("2", back.0.get("b").unwrap().value());
+}
+
+#[test]
+fn method_flags() {
+    use http::Method;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::method_flags")] Vec<Method>);
+
+    let allow = Wrap(vec![Method::GET, Method::POST, Method::DELETE]);
+    assert_eq!("11", serde_json::to_string(&allow).unwrap());
+    let back: Wrap = serde_json::from_str("11").unwrap();
+    assert_eq!(allow.0, back.0);
+    let back: Wrap = bincode::deserialize(&bincode::serialize(&allow).unwrap()).unwrap();
+    assert_eq!(allow.0, back.0);
+
+    let custom = Wrap(vec![Method::GET, "PROPFIND".parse().unwrap()]);
+    let json = serde_json::to_string(&custom).unwrap();
+    assert_eq!("[\"GET\",\"PROPFIND\"]", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(custom.0, back.0);
+    let back: Wrap = bincode::deserialize(&bincode::serialize(&custom).unwrap()).unwrap();
+    assert_eq!(custom.0, back.0);
+
+    assert!(serde_json::from_str::<Wrap>("1024").is_err());
+}
+
+#[test]
+fn status_code_retry() {
+    use http::StatusCode;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code_retry")] StatusCode);
+
+    assert_eq!(
+        "{\"code\":503,\"retryable\":true}",
+        serde_json::to_string(&Wrap(StatusCode::SERVICE_UNAVAILABLE)).unwrap()
+    );
+    assert_eq!(
+        "{\"code\":404,\"retryable\":false}",
+        serde_json::to_string(&Wrap(StatusCode::NOT_FOUND)).unwrap()
+    );
+
+    let back: Wrap = serde_json::from_str("{\"code\":503,\"retryable\":false}").unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, back.0);
+    let back: Wrap = serde_json::from_str("{\"code\":404}").unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, back.0);
+    let bin = bincode::serialize(&(Wrap(StatusCode::BAD_GATEWAY), 7u8)).unwrap();
+    let back: (Wrap, u8) = bincode::deserialize(&bin).unwrap();
+    assert_eq!((StatusCode::BAD_GATEWAY, 7), (back.0 .0, back.1));
+
+    fn only_404<S: serde::Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        http_serde::status_code_retry::serialize_with(status, &[404], ser)
+    }
+    #[derive(serde::Serialize)]
+    struct Custom(#[serde(serialize_with = "only_404")] StatusCode);
+    assert_eq!(
+        "{\"code\":404,\"retryable\":true}",
+        serde_json::to_string(&Custom(StatusCode::NOT_FOUND)).unwrap()
+    );
+}
+
+#[cfg(feature = "psl")]
+#[test]
+fn uri_psl() {
+    use http::Uri;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_psl")] Uri);
+
+    let json =
+        serde_json::to_string(&Wrap("https://www.example.co.uk/x".parse().unwrap())).unwrap();
+    assert_eq!(
+        "{\"scheme\":\"https\",\"subdomain\":\"www\",\"domain\":\"example.co.uk\",\"port\":null,\"path\":\"/x\"}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("https://www.example.co.uk/x", back.0);
+
+    let json = serde_json::to_string(&Wrap("http://example.com:8080/?q".parse().unwrap())).unwrap();
+    assert_eq!(
+        "{\"scheme\":\"http\",\"subdomain\":null,\"domain\":\"example.com\",\"port\":8080,\"path\":\"/?q\"}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("http://example.com:8080/?q", back.0);
+
+    let json = serde_json::to_string(&Wrap("http://127.0.0.1:8080/x".parse().unwrap())).unwrap();
+    assert_eq!(
+        "{\"scheme\":\"http\",\"subdomain\":null,\"domain\":\"127.0.0.1\",\"port\":8080,\"path\":\"/x\"}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("http://127.0.0.1:8080/x", back.0);
+
+    let json = serde_json::to_string(&Wrap("http://[::1]/x".parse().unwrap())).unwrap();
+    assert_eq!(
+        "{\"scheme\":\"http\",\"subdomain\":null,\"domain\":\"[::1]\",\"port\":null,\"path\":\"/x\"}",
+        json
+    );
+
+    let json = serde_json::to_string(&Wrap("http://localhost/x".parse().unwrap())).unwrap();
+    assert_eq!(
+        "{\"scheme\":\"http\",\"subdomain\":null,\"domain\":\"localhost\",\"port\":null,\"path\":\"/x\"}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("http://localhost/x", back.0);
+
+    assert!(serde_json::to_string(&Wrap("/relative".parse().unwrap())).is_err());
+}
+
+#[test]
+fn header_map_env() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_env")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert("content-type", HeaderValue::from_static("text/html"));
+    map.append("accept", HeaderValue::from_static("a/b"));
+    map.append("accept", HeaderValue::from_static("c/d"));
+
+    let json = serde_json::to_string(&Wrap(map)).unwrap();
+    assert_eq!(
+        "{\"CONTENT_TYPE\":\"text/html\",\"ACCEPT\":\"a/b, c/d\"}",
+        json
+    );
+
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("text/html", back.0["content-type"]);
+    assert_eq!("a/b, c/d", back.0["accept"]);
+    assert_eq!(2, back.0.len());
+}
+
+#[test]
+fn version_fallback() {
+    use http::Version;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::version")] Version);
+
+    // The fallback for versions unknown to this crate is the `Debug` string,
+    // which matches the regular representation of all the known ones.
+    for &v in &[
+        Version::HTTP_09,
+        Version::HTTP_10,
+        Version::HTTP_11,
+        Version::HTTP_2,
+        Version::HTTP_3,
+    ] {
+        let json = serde_json::to_string(&Wrap(v)).unwrap();
+        assert_eq!(format!("\"{:?}\"", v), json);
+        assert_eq!(v, serde_json::from_str::<Wrap>(&json).unwrap().0);
+    }
+
+    let err = serde_json::from_str::<Wrap>("\"HTTP/4.0\"").unwrap_err();
+    assert!(err.to_string().contains("HTTP/4.0"));
+
+    // A stand-in for a `Version` this crate doesn't know, which can't be constructed
+    struct StandIn(&'static str);
+
+    impl std::fmt::Debug for StandIn {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct Unknown(#[serde(serialize_with = "http_serde::version::serialize_unknown")] StandIn);
+
+    let json = serde_json::to_string(&Unknown(StandIn("HTTP/4.0"))).unwrap();
+    assert_eq!("\"HTTP/4.0\"", json);
+    let bin = bincode::serialize(&Unknown(StandIn("HTTP/4.0"))).unwrap();
+    assert_eq!(vec![255], bin);
+    // Unknown versions serialize, but don't deserialize
+    assert!(serde_json::from_str::<Wrap>(&json).is_err());
+    assert!(bincode::deserialize::<Wrap>(&bin).is_err());
+}
+
+#[cfg(feature = "retry_after")]
+#[test]
+fn retry_after() {
+    use http_serde::retry_after::RetryAfter;
+    use std::time::{Duration, SystemTime};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::retry_after")] RetryAfter);
+
+    let delay = Wrap(RetryAfter::Delay(Duration::from_secs(120)));
+    assert_eq!("\"120\"", serde_json::to_string(&delay).unwrap());
+    let back: Wrap = serde_json::from_str("\"120\"").unwrap();
+    assert_eq!(delay.0, back.0);
+    let back: Wrap = serde_json::from_str("120").unwrap();
+    assert_eq!(delay.0, back.0);
+
+    let at = SystemTime::UNIX_EPOCH + Duration::from_secs(1445412480);
+    let date = Wrap(RetryAfter::At(at));
+    let json = serde_json::to_string(&date).unwrap();
+    assert_eq!("\"Wed, 21 Oct 2015 07:28:00 GMT\"", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(date.0, back.0);
+
+    let back: Wrap = bincode::deserialize(&bincode::serialize(&date).unwrap()).unwrap();
+    assert_eq!(date.0, back.0);
+
+    assert!(serde_json::from_str::<Wrap>("\"soon\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("\"-5\"").is_err());
+}
+
+#[test]
+fn header_map_ascii() {
+    use http::HeaderMap;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_ascii")] HeaderMap);
+
+    let back: Wrap = serde_json::from_str("{\"x-plain\":\"hello\"}").unwrap();
+    assert_eq!("hello", back.0["x-plain"]);
+
+    assert!(serde_json::from_str::<Wrap>("{\"x-bin\":[[104,105,128]]}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"x-utf\":\"h\u{e9}\"}").is_err());
+
+    let mut map = HeaderMap::new();
+    map.insert("x-bin", http::HeaderValue::from_bytes(b"hi\x80").unwrap());
+    let bin = bincode::serialize(&Wrap(map)).unwrap();
+    assert!(bincode::deserialize::<Wrap>(&bin).is_err());
+}
+
+#[cfg(all(feature = "url", feature = "std"))]
+#[test]
+fn uri_as_url() {
+    use http::Uri;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_as_url")] Uri);
+
+    let uri: Uri = "https://Example.com:443/a/./b?x=1".parse().unwrap();
+    let json = serde_json::to_string(&Wrap(uri)).unwrap();
+    assert_eq!("\"https://example.com/a/b?x=1\"", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("https://example.com/a/b?x=1", back.0);
+
+    let back: Wrap = serde_json::from_str("\"http://example.com/p#frag\"").unwrap();
+    assert_eq!("http://example.com/p", back.0);
+
+    assert!(serde_json::to_string(&Wrap("/relative".parse().unwrap())).is_err());
+    assert!(serde_json::from_str::<Wrap>("\"/relative\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("\"mailto:x@example.com\"").is_err());
+}
+
+#[test]
+fn status_code_i18n() {
+    use http::StatusCode;
+    use http_serde::status_code_i18n::key;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code_i18n")] StatusCode);
+
+    let json = serde_json::to_string(&Wrap(StatusCode::NOT_FOUND)).unwrap();
+    assert_eq!(
+        "{\"code\":404,\"reason\":\"Not Found\",\"key\":\"not_found\"}",
+        json
+    );
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        serde_json::from_str::<Wrap>("{\"code\":404}").unwrap().0
+    );
+
+    assert_eq!(
+        Some("service_unavailable"),
+        key(StatusCode::SERVICE_UNAVAILABLE).as_deref()
+    );
+    assert_eq!(
+        Some("non_authoritative_information"),
+        key(StatusCode::NON_AUTHORITATIVE_INFORMATION).as_deref()
+    );
+    assert_eq!(Some("im_a_teapot"), key(StatusCode::IM_A_TEAPOT).as_deref());
+
+    let custom = StatusCode::from_u16(599).unwrap();
+    let json = serde_json::to_string(&Wrap(custom)).unwrap();
+    assert_eq!("{\"code\":599,\"reason\":null,\"key\":null}", json);
+
+    let bin = bincode::serialize(&Wrap(StatusCode::SERVICE_UNAVAILABLE)).unwrap();
+    assert_eq!(
+        StatusCode::SERVICE_UNAVAILABLE,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+}
+
+#[test]
+fn header_map_require() {
+    use http::HeaderMap;
+
+    fn host<'de, D: serde::Deserializer<'de>>(de: D) -> Result<HeaderMap, D::Error> {
+        http_serde::header_map_require(&["Host"]).deserialize(de)
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Wrap(#[serde(deserialize_with = "host")] HeaderMap);
+
+    let back: Wrap = serde_json::from_str("{\"host\":\"example.com\",\"accept\":\"*/*\"}").unwrap();
+    assert_eq!("example.com", back.0["host"]);
+
+    let err = serde_json::from_str::<Wrap>("{\"accept\":\"*/*\"}")
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("missing required header `Host`"));
+}
+
+#[test]
+fn method_u8() {
+    use http::Method;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::method_u8")] Method);
+
+    let pinned = [
+        (Method::OPTIONS, 0u8),
+        (Method::GET, 1),
+        (Method::POST, 2),
+        (Method::PUT, 3),
+        (Method::DELETE, 4),
+        (Method::HEAD, 5),
+        (Method::TRACE, 6),
+        (Method::CONNECT, 7),
+        (Method::PATCH, 8),
+    ];
+    for (method, byte) in pinned.iter() {
+        let bin = bincode::serialize(&Wrap(method.clone())).unwrap();
+        assert_eq!(vec![*byte], bin);
+        assert_eq!(*method, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+        assert_eq!(
+            byte.to_string(),
+            serde_json::to_string(&Wrap(method.clone())).unwrap()
+        );
+    }
+
+    assert!(bincode::serialize(&Wrap("PROPFIND".parse().unwrap())).is_err());
+    assert!(bincode::deserialize::<Wrap>(&[9]).is_err());
+}
+
+#[test]
+fn uri_sorted_query() {
+    use http::Uri;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_sorted_query")] Uri);
+
+    let sorted = |uri: &str| serde_json::to_string(&Wrap(uri.parse().unwrap())).unwrap();
+
+    assert_eq!("\"http://x/p?a=1&b=2\"", sorted("http://x/p?b=2&a=1"));
+    assert_eq!("\"/p?a=%20&x=2&x=1&y\"", sorted("/p?x=2&y&&a=%20&x=1"));
+    assert_eq!("\"http://x/?a\"", sorted("http://x?a"));
+    assert_eq!("\"http://x/\"", sorted("http://x/?&"));
+    assert_eq!("\"http://x/\"", sorted("http://x/"));
+
+    let back: Wrap = serde_json::from_str("\"http://x/p?a=1&b=2\"").unwrap();
+    assert_eq!("http://x/p?a=1&b=2", back.0);
+}
+
+#[test]
+fn header_map_indexed() {
+    use http::header::HeaderName;
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_indexed")] Vec<(HeaderName, HeaderValue)>);
+
+    let headers = vec![
+        (HeaderName::from_static("a"), HeaderValue::from_static("1")),
+        (HeaderName::from_static("b"), HeaderValue::from_static("2")),
+        (
+            HeaderName::from_static("a"),
+            HeaderValue::from_bytes(b"\xff").unwrap(),
+        ),
+    ];
+    let json = serde_json::to_string(&Wrap(headers.clone())).unwrap();
+    assert_eq!(
+        "[{\"index\":0,\"name\":\"a\",\"value\":\"1\"},{\"index\":1,\"name\":\"b\",\"value\":\"2\"},{\"index\":2,\"name\":\"a\",\"value\":[255]}]",
+        json
+    );
+    assert_eq!(headers, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let bin = bincode::serialize(&Wrap(headers.clone())).unwrap();
+    assert_eq!(headers, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+
+    let shuffled = "[{\"index\":2,\"name\":\"a\",\"value\":\"3\"},{\"index\":0,\"name\":\"a\",\"value\":\"1\"},{\"index\":1,\"name\":\"b\",\"value\":\"2\"}]";
+    let back = serde_json::from_str::<Wrap>(shuffled).unwrap().0;
+    let order: Vec<_> = back
+        .iter()
+        .map(|(n, v)| (n.as_str(), v.to_str().unwrap()))
+        .collect();
+    assert_eq!(vec![("a", "1"), ("b", "2"), ("a", "3")], order);
+    let map: HeaderMap = back.into_iter().collect();
+    assert_eq!(2, map.get_all("a").iter().count());
+
+    assert!(serde_json::from_str::<Wrap>(
+        "[{\"index\":0,\"name\":\"a\",\"value\":\"1\"},{\"index\":0,\"name\":\"b\",\"value\":\"2\"}]"
+    )
+    .is_err());
+}
+
+#[test]
+fn status_code_or_any() {
+    use http::StatusCode;
+    use http_serde::status_code_or_any::StatusMatch;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code_or_any")] StatusMatch);
+
+    assert_eq!(
+        StatusMatch::Any,
+        serde_json::from_str::<Wrap>("\"default\"").unwrap().0
+    );
+    assert_eq!(
+        StatusMatch::Exact(StatusCode::NOT_FOUND),
+        serde_json::from_str::<Wrap>("404").unwrap().0
+    );
+    assert_eq!(
+        StatusMatch::Exact(StatusCode::NOT_FOUND),
+        serde_json::from_str::<Wrap>("\"404\"").unwrap().0
+    );
+    assert!(serde_json::from_str::<Wrap>("\"any\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("1000").is_err());
+
+    assert_eq!(
+        "\"default\"",
+        serde_json::to_string(&Wrap(StatusMatch::Any)).unwrap()
+    );
+    assert_eq!(
+        "404",
+        serde_json::to_string(&Wrap(StatusCode::NOT_FOUND.into())).unwrap()
+    );
+
+    for m in [StatusMatch::Any, StatusMatch::Exact(StatusCode::OK)].iter() {
+        let bin = bincode::serialize(&Wrap(*m)).unwrap();
+        assert_eq!(*m, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+    }
+    assert!(StatusMatch::Any.matches(StatusCode::IM_A_TEAPOT));
+    assert!(!StatusMatch::Exact(StatusCode::OK).matches(StatusCode::IM_A_TEAPOT));
+}
+
+#[cfg(feature = "idna")]
+#[test]
+fn uri_idna() {
+    use http::Uri;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_idna")] Uri);
+
+    let back: Wrap = serde_json::from_str("\"https://例え.jp/path?q=1\"").unwrap();
+    assert_eq!("https://xn--r8jz45g.jp/path?q=1", back.0);
+    assert_eq!(
+        "\"https://xn--r8jz45g.jp/path?q=1\"",
+        serde_json::to_string(&back).unwrap()
+    );
+
+    let back: Wrap = serde_json::from_str("\"http://user@例え.jp:8080\"").unwrap();
+    assert_eq!("http://user@xn--r8jz45g.jp:8080/", back.0);
+
+    let uri: Uri = "HTTP://Example.COM:80/A?B".parse().unwrap();
+    assert_eq!(
+        "\"http://example.com:80/A?B\"",
+        serde_json::to_string(&Wrap(uri)).unwrap()
+    );
+    let back: Wrap = serde_json::from_str("\"/just/a/path\"").unwrap();
+    assert_eq!("/just/a/path", back.0);
+    let back: Wrap = serde_json::from_str("\"http://[::1]:80/\"").unwrap();
+    assert_eq!("http://[::1]:80/", back.0);
+
+    assert!(serde_json::from_str::<Wrap>("\"https://xn--a.jp/\"").is_err());
+}
+
+#[test]
+fn header_map_lossy_display() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_lossy_display")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert("x-bin", HeaderValue::from_bytes(b"caf\xe9").unwrap());
+    map.append("x-multi", HeaderValue::from_static("ok"));
+    map.append("x-multi", HeaderValue::from_bytes(b"\xff\xfe").unwrap());
+
+    let json = serde_json::to_string(&Wrap(map)).unwrap();
+    assert_eq!(
+        "{\"x-bin\":\"caf\u{fffd}\",\"x-multi\":[\"ok\",\"\u{fffd}\u{fffd}\"]}",
+        json
+    );
+    assert!(serde_json::from_str::<Wrap>(&json).is_err());
+}
+
+#[test]
+fn route_key() {
+    use http::Method;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::route_key")] (Method, String));
+
+    let json = serde_json::to_string(&Wrap((Method::POST, "/a/b".into()))).unwrap();
+    assert_eq!("\"POST /a/b\"", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!((Method::POST, "/a/b".to_string()), back.0);
+
+    let back: Wrap = serde_json::from_str("\"GET /search/{query with spaces}\"").unwrap();
+    assert_eq!(
+        (Method::GET, "/search/{query with spaces}".to_string()),
+        back.0
+    );
+
+    assert!(serde_json::from_str::<Wrap>("\"GET\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("\"G(T /\"").is_err());
+}
+
+#[cfg(feature = "etag")]
+#[test]
+fn response_with_etag() {
+    use http::{Response, StatusCode};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::response_with_etag")] Response<Vec<u8>>);
+
+    let res = Response::builder()
+        .status(StatusCode::CREATED)
+        .header("content-type", "text/plain")
+        .body(b"hello".to_vec())
+        .unwrap();
+    let json = serde_json::to_string(&Wrap(res)).unwrap();
+    assert_eq!(
+        "{\"status\":201,\"version\":\"HTTP/1.1\",\"headers\":{\"content-type\":\"text/plain\",\"etag\":\"W/\\\"fnv-a430d84680aabd0b\\\"\"},\"body\":\"hello\"}",
+        json
+    );
+
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(StatusCode::CREATED, back.0.status());
+    assert_eq!(b"hello", &back.0.body()[..]);
+    assert_eq!(
+        http_serde::response_with_etag::etag(b"hello"),
+        back.0.headers()["etag"]
+    );
+
+    let tampered = json.replace("\"hello\"", "\"jello\"");
+    assert!(serde_json::from_str::<Wrap>(&tampered).is_err());
+
+    let origin = Response::builder()
+        .header("etag", "\"v1\"")
+        .body(vec![0xff, 0])
+        .unwrap();
+    let bin = bincode::serialize(&Wrap(origin)).unwrap();
+    let back: Wrap = bincode::deserialize(&bin).unwrap();
+    assert_eq!("\"v1\"", back.0.headers()["etag"]);
+    assert_eq!(vec![0xff, 0], *back.0.body());
+
+    let cbor = serde_cbor::to_vec(&Wrap(Response::new(b"hello".to_vec()))).unwrap();
+    let back: Wrap = serde_cbor::from_slice(&cbor).unwrap();
+    assert_eq!(b"hello", &back.0.body()[..]);
+    assert_eq!(
+        http_serde::response_with_etag::etag(b"hello"),
+        back.0.headers()["etag"]
+    );
+}
+
+#[test]
+fn header_map_logfmt() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_logfmt")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert("host", HeaderValue::from_static("example.com"));
+    map.insert("accept", HeaderValue::from_static("*/*"));
+    map.insert("referer", HeaderValue::from_static("https://example.com/"));
+    map.insert("user-agent", HeaderValue::from_static("curl/8.0"));
+
+    let json = serde_json::to_string(&Wrap(map)).unwrap();
+    assert_eq!(
+        "[\"curl/8.0\",\"https://example.com/\",\"example.com\"]",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(3, back.0.len());
+    assert_eq!("curl/8.0", back.0["user-agent"]);
+
+    let back: Wrap = serde_json::from_str("[null,null,\"example.com\"]").unwrap();
+    assert_eq!(1, back.0.len());
+    assert!(serde_json::from_str::<Wrap>("[null,null]").is_err());
+
+    fn host_only<S: serde::Serializer>(h: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        http_serde::header_map_logfmt::serialize_with(h, &["host"], ser)
+    }
+    #[derive(serde::Serialize)]
+    struct Custom(#[serde(serialize_with = "host_only")] HeaderMap);
+    assert_eq!(
+        "[\"example.com\"]",
+        serde_json::to_string(&Custom(back.0)).unwrap()
+    );
+}
+
+#[test]
+fn uri_exact_path() {
+    use http::Uri;
+    use http_serde::uri_exact_path::RawUri;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Plain(#[serde(with = "http_serde::uri")] Uri);
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Exact(#[serde(with = "http_serde::uri_exact_path")] RawUri);
+
+    // `Uri` normalizes the empty path to `/`
+    let empty: Plain = serde_json::from_str("\"http://x\"").unwrap();
+    let root: Plain = serde_json::from_str("\"http://x/\"").unwrap();
+    assert_eq!("\"http://x/\"", serde_json::to_string(&empty).unwrap());
+    assert_eq!("\"http://x/\"", serde_json::to_string(&root).unwrap());
+
+    for &json in &[
+        "\"http://x\"",
+        "\"http://x/\"",
+        "\"http://x?q\"",
+        "\"http://x/?q\"",
+    ] {
+        let exact: Exact = serde_json::from_str(json).unwrap();
+        assert_eq!(json, serde_json::to_string(&exact).unwrap());
+        assert_eq!("/", exact.0.uri().path());
+    }
+}
+
+#[test]
+fn status_code_problem() {
+    use http::StatusCode;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Problem {
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(flatten, with = "http_serde::status_code_problem")]
+        status: StatusCode,
+    }
+
+    let json = serde_json::to_string(&Problem {
+        kind: "about:blank".into(),
+        status: StatusCode::NOT_FOUND,
+    })
+    .unwrap();
+    assert_eq!(
+        "{\"type\":\"about:blank\",\"status\":404,\"title\":\"Not Found\"}",
+        json
+    );
+    let back: Problem = serde_json::from_str(&json).unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, back.status);
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code_problem")] StatusCode);
+
+    let json = serde_json::to_string(&Wrap(StatusCode::from_u16(599).unwrap())).unwrap();
+    assert_eq!("{\"status\":599,\"title\":null}", json);
+    let back: Wrap = serde_json::from_str("{\"status\":599}").unwrap();
+    assert_eq!(599, back.0.as_u16());
+}
+
+#[test]
+fn header_map_raw_block() {
+    use http::{HeaderMap, HeaderValue};
+    use http_serde::header_map_raw_block::{from_bytes, to_bytes};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_raw_block")] HeaderMap);
+
+    let block = b"host: example.com\r\nx-bin: caf\xe9\r\n\r\n";
+    let map = from_bytes(block).unwrap();
+    assert_eq!("example.com", map["host"]);
+    assert_eq!(&b"caf\xe9"[..], map["x-bin"].as_bytes());
+    assert_eq!(&block[..], &to_bytes(&map)[..]);
+
+    let bin = bincode::serialize(&Wrap(map)).unwrap();
+    let back: Wrap = bincode::deserialize(&bin).unwrap();
+    assert_eq!(&block[..], &to_bytes(&back.0)[..]);
+
+    let mut map = HeaderMap::new();
+    map.insert("host", HeaderValue::from_static("example.com"));
+    map.insert("accept", HeaderValue::from_static("*/*"));
+    let json = serde_json::to_string(&Wrap(map)).unwrap();
+    assert_eq!("\"host: example.com\\r\\naccept: */*\\r\\n\\r\\n\"", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("*/*", back.0["accept"]);
+
+    let folded = from_bytes(b"X-Long: a\r\n  b\r\n\tc\nAccept:  */* \n").unwrap();
+    assert_eq!("a b c", folded["x-long"]);
+    assert_eq!("*/*", folded["accept"]);
+
+    assert!(from_bytes(b"host: x\r\n\r\nbody").is_err());
+    assert!(from_bytes(b" folded: x\r\n").is_err());
+    assert!(from_bytes(b"no colon\r\n").is_err());
+}
+
+#[test]
+fn version_protocol_tagged() {
+    use http::Version;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::version_protocol_tagged")] Version);
+
+    let json = serde_json::to_string(&Wrap(Version::HTTP_11)).unwrap();
+    assert_eq!("{\"protocol\":\"http\",\"version\":\"1.1\"}", json);
+    assert_eq!(
+        Version::HTTP_11,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    let json = serde_json::to_string(&Wrap(Version::HTTP_2)).unwrap();
+    assert_eq!("{\"protocol\":\"http\",\"version\":\"2\"}", json);
+    assert_eq!(
+        Version::HTTP_2,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    let bin = bincode::serialize(&Wrap(Version::HTTP_2)).unwrap();
+    assert_eq!(
+        Version::HTTP_2,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+
+    assert!(serde_json::from_str::<Wrap>("{\"protocol\":\"http\",\"version\":\"4\"}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"protocol\":\"spdy\",\"version\":\"3\"}").is_err());
+}
+
+#[test]
+fn uri_valid_host() {
+    use http::Uri;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_valid_host")] Uri);
+
+    for &ok in &[
+        "\"https://www.example.com/x\"",
+        "\"http://xn--r8jz45g.jp.\"",
+        "\"http://192.168.0.1:8080/\"",
+        "\"http://[::1]/\"",
+        "\"/relative\"",
+    ] {
+        serde_json::from_str::<Wrap>(ok).unwrap();
+    }
+    for &bad in &[
+        "\"http://exa mple.com/\"",
+        "\"http://exa%20mple.com/\"",
+        "\"http://exa_mple.com/\"",
+        "\"http://-bad.com/\"",
+        "\"http://a..b/\"",
+        "\"http://1.2.3.999/\"",
+        "\"http://[nope]/\"",
+    ] {
+        assert!(serde_json::from_str::<Wrap>(bad).is_err(), "{}", bad);
+    }
+
+    use http_serde::uri_valid_host::is_valid_host;
+    for &ok in &[
+        "0.0.0.0",
+        "255.255.255.255",
+        "[::]",
+        "[1:2:3:4:5:6:7:8]",
+        "[1::8]",
+        "[1:2:3:4:5:6:7::]",
+        "[::ffff:192.0.2.1]",
+        "[1:2:3:4:5:6:1.2.3.4]",
+        "[fe80::AbCd]",
+    ] {
+        assert!(is_valid_host(ok), "{}", ok);
+    }
+    for &bad in &[
+        "01.2.3.4",
+        "[1:2:3:4:5:6:7]",
+        "[1:2:3:4:5:6:7:8:9]",
+        "[1::2::3]",
+        "[:1::2]",
+        "[12345::]",
+        "[::1.2.3.4:1]",
+        "[1.2.3.4::]",
+        "[1:2:3:4:5:6:7::8]",
+    ] {
+        assert!(!is_valid_host(bad), "{}", bad);
+    }
+}
+
+#[test]
+fn header_map_dup() {
+    use http::HeaderMap;
+    use http_serde::DupStrategy;
+
+    let payload = "{\"x\":\"a\",\"y\":\"1\",\"x\":[\"b\",\"c\"]}";
+    let values = |strategy: DupStrategy| {
+        let mut de = serde_json::Deserializer::from_str(payload);
+        http_serde::header_map_dup(strategy)
+            .deserialize(&mut de)
+            .map(|map: HeaderMap| {
+                assert_eq!("1", map["y"]);
+                map.get_all("x")
+                    .iter()
+                    .map(|v| v.to_str().unwrap().to_owned())
+                    .collect::<Vec<_>>()
+            })
+    };
+
+    assert_eq!(vec!["a", "b", "c"], values(DupStrategy::Append).unwrap());
+    assert_eq!(vec!["a"], values(DupStrategy::FirstWins).unwrap());
+    assert_eq!(vec!["b", "c"], values(DupStrategy::LastWins).unwrap());
+    assert!(values(DupStrategy::Error)
+        .unwrap_err()
+        .to_string()
+        .contains("duplicate header `x`"));
+    assert_eq!(DupStrategy::Append, DupStrategy::default());
+
+    // Plain `header_map` keeps its own last-wins behavior for single values
+    #[derive(serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map")] HeaderMap);
+    let map = serde_json::from_str::<Wrap>("{\"x\":\"a\",\"x\":\"b\"}")
+        .unwrap()
+        .0;
+    assert_eq!(vec!["b"], map.get_all("x").iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn method_classified() {
+    use http::Method;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::method_classified")] Method);
+
+    let json = serde_json::to_string(&Wrap(Method::GET)).unwrap();
+    assert_eq!("{\"method\":\"GET\",\"standard\":true}", json);
+    assert_eq!(Method::GET, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let propfind: Method = "PROPFIND".parse().unwrap();
+    let json = serde_json::to_string(&Wrap(propfind.clone())).unwrap();
+    assert_eq!("{\"method\":\"PROPFIND\",\"standard\":false}", json);
+    let back: Wrap = serde_json::from_str("{\"method\":\"PROPFIND\",\"standard\":true}").unwrap();
+    assert_eq!(propfind, back.0);
+
+    let bin = bincode::serialize(&Wrap(propfind.clone())).unwrap();
+    assert_eq!(propfind, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+}
+
+#[test]
+fn status_code_i32() {
+    use http::StatusCode;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code_i32")] StatusCode);
+
+    assert_eq!("200", serde_json::to_string(&Wrap(StatusCode::OK)).unwrap());
+    assert_eq!(
+        StatusCode::OK,
+        serde_json::from_str::<Wrap>("200").unwrap().0
+    );
+    let bin = bincode::serialize(&Wrap(StatusCode::OK)).unwrap();
+    assert_eq!(200i32.to_le_bytes().to_vec(), bin);
+    assert_eq!(
+        StatusCode::OK,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+
+    assert!(serde_json::from_str::<Wrap>("-1").is_err());
+    assert!(serde_json::from_str::<Wrap>("99").is_err());
+    assert!(serde_json::from_str::<Wrap>("600").is_err());
+    assert!(bincode::deserialize::<Wrap>(&(-1i32).to_le_bytes()).is_err());
+}
+
+#[test]
+fn header_name_and_value() {
+    use http::header::HeaderName;
+    use http::HeaderValue;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(
+        #[serde(with = "http_serde::header_name")] HeaderName,
+        #[serde(with = "http_serde::header_value")] HeaderValue,
+    );
+
+    let text = Wrap(
+        HeaderName::from_static("x-rule"),
+        HeaderValue::from_static("matched"),
+    );
+    let json = serde_json::to_string(&text).unwrap();
+    assert_eq!("[\"x-rule\",\"matched\"]", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!((text.0.clone(), text.1.clone()), (back.0, back.1));
+    let back: Wrap = serde_json::from_str("[\"X-Rule\",\"matched\"]").unwrap();
+    assert_eq!("x-rule", back.0);
+
+    let binary = Wrap(
+        HeaderName::from_static("x-bin"),
+        HeaderValue::from_bytes(b"\xfe\xff").unwrap(),
+    );
+    let json = serde_json::to_string(&binary).unwrap();
+    assert_eq!("[\"x-bin\",[254,255]]", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(binary.1, back.1);
+    let yaml = serde_yaml::to_string(&binary).unwrap();
+    let back: Wrap = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(binary.1, back.1);
+
+    for w in [text, binary].iter() {
+        let bin = bincode::serialize(w).unwrap();
+        let back: Wrap = bincode::deserialize(&bin).unwrap();
+        assert_eq!((&w.0, &w.1), (&back.0, &back.1));
+        let cbor = serde_cbor::to_vec(w).unwrap();
+        let back: Wrap = serde_cbor::from_slice(&cbor).unwrap();
+        assert_eq!((&w.0, &w.1), (&back.0, &back.1));
+    }
+
+    assert!(serde_json::from_str::<Wrap>("[\"bad name\",\"x\"]").is_err());
+    assert!(serde_json::from_str::<Wrap>("[\"x\",\"bad\\nvalue\"]").is_err());
+}
+
+#[test]
+fn uri_https_only() {
+    use http::Uri;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_https_only")] Uri);
+
+    let back: Wrap = serde_json::from_str("\"https://x/\"").unwrap();
+    assert_eq!("https://x/", back.0);
+    assert_eq!("\"https://x/\"", serde_json::to_string(&back).unwrap());
+
+    let err = serde_json::from_str::<Wrap>("\"http://x/\"").unwrap_err();
+    assert!(
+        err.to_string().contains("upgrade it to `https://x/`"),
+        "{}",
+        err
+    );
+    let err = serde_json::from_str::<Wrap>("\"/relative\"").unwrap_err();
+    assert!(err.to_string().contains("no scheme"), "{}", err);
+    assert!(serde_json::from_str::<Wrap>("\"ftp://x/\"").is_err());
+}
+
+#[test]
+fn header_map_shaped() {
+    use http::{HeaderMap, HeaderValue};
+    use http_serde::HeaderMapShape;
+
+    let mut map = HeaderMap::new();
+    map.insert("single", HeaderValue::from_static("one"));
+    map.append("multi", HeaderValue::from_static("a"));
+    map.append("multi", HeaderValue::from_static("b"));
+
+    let shaped = |shape: HeaderMapShape| {
+        let json = http_serde::header_map_shaped(shape)
+            .serialize(&map, serde_json::value::Serializer)
+            .unwrap()
+            .to_string();
+        let back: HeaderMap = http_serde::header_map_shaped(shape)
+            .deserialize(serde_json::from_str::<serde_json::Value>(&json).unwrap())
+            .unwrap();
+        (json, back)
+    };
+
+    let (json, back) = shaped(HeaderMapShape::Auto);
+    assert_eq!("{\"multi\":[\"a\",\"b\"],\"single\":\"one\"}", json);
+    assert_eq!(2, back.get_all("multi").iter().count());
+
+    let (json, back) = shaped(HeaderMapShape::AlwaysArray);
+    assert_eq!("{\"multi\":[\"a\",\"b\"],\"single\":[\"one\"]}", json);
+    assert_eq!("one", back["single"]);
+    assert_eq!(2, back.get_all("multi").iter().count());
+
+    let (json, back) = shaped(HeaderMapShape::AlwaysScalarJoin);
+    assert_eq!("{\"multi\":\"a, b\",\"single\":\"one\"}", json);
+    assert_eq!("a, b", back["multi"]);
+    assert_eq!(1, back.get_all("multi").iter().count());
+
+    let mut binary = HeaderMap::new();
+    binary.insert("x-bin", HeaderValue::from_bytes(b"\xff").unwrap());
+    let json = http_serde::header_map_shaped(HeaderMapShape::AlwaysArray)
+        .serialize(&binary, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!("{\"x-bin\":[[255]]}", json.to_string());
+    let back = http_serde::header_map::deserialize(json).unwrap();
+    assert_eq!(binary, back);
+    assert!(
+        http_serde::header_map_shaped(HeaderMapShape::AlwaysScalarJoin)
+            .serialize(&binary, serde_json::value::Serializer)
+            .is_err()
+    );
+}
+
+#[test]
+fn scheme_and_path_and_query() {
+    use http::uri::{PathAndQuery, Scheme};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap {
+        #[serde(with = "http_serde::scheme")]
+        scheme: Scheme,
+        #[serde(with = "http_serde::path_and_query")]
+        pq: PathAndQuery,
+        #[serde(with = "http_serde::option::scheme", default)]
+        opt_scheme: Option<Scheme>,
+        #[serde(with = "http_serde::option::path_and_query", default)]
+        opt_pq: Option<PathAndQuery>,
+    }
+
+    let w = Wrap {
+        scheme: Scheme::HTTPS,
+        pq: "/a/b?c=d".parse().unwrap(),
+        opt_scheme: Some("custom".parse().unwrap()),
+        opt_pq: None,
+    };
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!(
+        "{\"scheme\":\"https\",\"pq\":\"/a/b?c=d\",\"opt_scheme\":\"custom\",\"opt_pq\":null}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(Scheme::HTTPS, back.scheme);
+    assert_eq!("/a/b?c=d", back.pq);
+    assert_eq!("custom", back.opt_scheme.unwrap().as_str());
+    assert!(back.opt_pq.is_none());
+
+    let back: Wrap = bincode::deserialize(&bincode::serialize(&w).unwrap()).unwrap();
+    assert_eq!("/a/b?c=d", back.pq);
+
+    let back: Wrap =
+        serde_json::from_str("{\"scheme\":\"http\",\"pq\":\"\",\"opt_pq\":\"*\"}").unwrap();
+    assert_eq!("/", back.pq);
+    assert_eq!(PathAndQuery::from_static("/"), back.pq);
+    assert_eq!("*", back.opt_pq.unwrap());
+    assert!(back.opt_scheme.is_none());
+
+    assert!(serde_json::from_str::<Wrap>("{\"scheme\":\"http\",\"pq\":\"rel\"}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"scheme\":\"\",\"pq\":\"/\"}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"scheme\":\"a b\",\"pq\":\"/\"}").is_err());
+}
+
+#[test]
+fn option() {
+    use http::{HeaderMap, Method, StatusCode, Uri, Version};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap {
+        #[serde(with = "http_serde::option::status_code", default)]
+        status: Option<StatusCode>,
+        #[serde(with = "http_serde::option::method", default)]
+        method: Option<Method>,
+        #[serde(with = "http_serde::option::uri", default)]
+        uri: Option<Uri>,
+        #[serde(with = "http_serde::option::version", default)]
+        version: Option<Version>,
+        #[serde(with = "http_serde::option::header_map", default)]
+        headers: Option<HeaderMap>,
+    }
+
+    let w = Wrap {
+        status: Some(StatusCode::NOT_FOUND),
+        method: None,
+        uri: Some("/x".parse().unwrap()),
+        version: Some(Version::HTTP_11),
+        headers: None,
+    };
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!(
+        "{\"status\":404,\"method\":null,\"uri\":\"/x\",\"version\":\"HTTP/1.1\",\"headers\":null}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(Some(StatusCode::NOT_FOUND), back.status);
+    assert_eq!(Some(Version::HTTP_11), back.version);
+    assert!(back.method.is_none());
+
+    let back: Wrap = bincode::deserialize(&bincode::serialize(&w).unwrap()).unwrap();
+    assert_eq!(Some(StatusCode::NOT_FOUND), back.status);
+    assert_eq!("/x", back.uri.unwrap());
+
+    let back: Wrap = serde_json::from_str("{}").unwrap();
+    assert!(back.status.is_none() && back.uri.is_none());
+}
+
+#[test]
+fn version_capabilities() {
+    use http::Version;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::version_capabilities")] Version);
+
+    let json = serde_json::to_string(&Wrap(Version::HTTP_11)).unwrap();
+    assert_eq!("{\"version\":\"HTTP/1.1\",\"multiplexed\":false}", json);
+    let json = serde_json::to_string(&Wrap(Version::HTTP_2)).unwrap();
+    assert_eq!("{\"version\":\"HTTP/2.0\",\"multiplexed\":true}", json);
+    assert_eq!(
+        Version::HTTP_2,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    let back: Wrap = serde_json::from_str("{\"version\":\"HTTP/1.0\"}").unwrap();
+    assert_eq!(Version::HTTP_10, back.0);
+
+    let bin = bincode::serialize(&Wrap(Version::HTTP_3)).unwrap();
+    assert_eq!(
+        Version::HTTP_3,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+}
+
+#[test]
+fn request_and_response_parts() {
+    use http::{request, response, Request, Response, StatusCode, Version};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap {
+        #[serde(with = "http_serde::request_parts")]
+        req: request::Parts,
+        #[serde(with = "http_serde::response_parts")]
+        res: response::Parts,
+    }
+
+    let mut req = Request::post("https://example.com/a?b")
+        .version(Version::HTTP_2)
+        .header("x-a", "1")
+        .header("x-a", "2")
+        .body(())
+        .unwrap();
+    req.extensions_mut().insert(5u8);
+    let res = Response::builder()
+        .status(StatusCode::CREATED)
+        .header("location", "/a/1")
+        .body(())
+        .unwrap();
+    let w = Wrap {
+        req: req.into_parts().0,
+        res: res.into_parts().0,
+    };
+
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!(
+        "{\"req\":{\"method\":\"POST\",\"uri\":\"https://example.com/a?b\",\"version\":\"HTTP/2.0\",\"headers\":{\"x-a\":[\"1\",\"2\"]}},\
+         \"res\":{\"status\":201,\"version\":\"HTTP/1.1\",\"headers\":{\"location\":\"/a/1\"}}}",
+        json
+    );
+
+    for back in [
+        serde_json::from_str::<Wrap>(&json).unwrap(),
+        bincode::deserialize::<Wrap>(&bincode::serialize(&w).unwrap()).unwrap(),
+    ] {
+        assert_eq!(w.req.method, back.req.method);
+        assert_eq!(w.req.uri, back.req.uri);
+        assert_eq!(Version::HTTP_2, back.req.version);
+        assert_eq!(w.req.headers, back.req.headers);
+        assert!(back.req.extensions.get::<u8>().is_none());
+        assert_eq!(StatusCode::CREATED, back.res.status);
+        assert_eq!(w.res.headers, back.res.headers);
+    }
+}
+
+#[test]
+fn uri_redact_params() {
+    use http::Uri;
+
+    fn redacted<S: serde::Serializer>(uri: &Uri, ser: S) -> Result<S::Ok, S::Error> {
+        http_serde::uri_redact_params(&["token", "api_key"]).serialize(uri, ser)
+    }
+
+    fn read_redacted<'de, D: serde::Deserializer<'de>>(de: D) -> Result<Uri, D::Error> {
+        http_serde::uri_redact_params(&["token", "api_key"]).deserialize(de)
+    }
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(serialize_with = "redacted", deserialize_with = "read_redacted")] Uri);
+
+    let uri: Uri = "https://x/path?token=abc&page=1".parse().unwrap();
+    let json = serde_json::to_string(&Wrap(uri)).unwrap();
+    assert_eq!("\"https://x/path?token=REDACTED&page=1\"", json);
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!("https://x/path?token=REDACTED&page=1", back.0);
+
+    let back: Wrap = serde_json::from_str("\"/p?a=1&api_key=secret&token\"").unwrap();
+    assert_eq!("/p?a=1&api_key=REDACTED&token", back.0);
+
+    let json = serde_json::to_string(&Wrap("/p?tokens=1".parse().unwrap())).unwrap();
+    assert_eq!("\"/p?tokens=1\"", json);
+}
+
+#[cfg(feature = "serde_with")]
+#[test]
+fn serde_with_as() {
+    use http::{HeaderMap, Method, StatusCode, Uri};
+    use http_serde::As;
+    use std::collections::BTreeMap;
+
+    #[serde_with::serde_as]
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap {
+        #[serde_as(as = "Vec<As<Uri>>")]
+        uris: Vec<Uri>,
+        #[serde_as(as = "BTreeMap<_, As<StatusCode>>")]
+        statuses: BTreeMap<String, StatusCode>,
+        #[serde_as(as = "Option<As<Method>>")]
+        method: Option<Method>,
+        #[serde_as(as = "As<HeaderMap>")]
+        headers: HeaderMap,
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-a", "1".parse().unwrap());
+    let w = Wrap {
+        uris: vec!["/a".parse().unwrap(), "https://b/".parse().unwrap()],
+        statuses: vec![("ok".to_string(), StatusCode::OK)]
+            .into_iter()
+            .collect(),
+        method: Some(Method::PATCH),
+        headers,
+    };
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!(
+        "{\"uris\":[\"/a\",\"https://b/\"],\"statuses\":{\"ok\":200},\"method\":\"PATCH\",\"headers\":{\"x-a\":\"1\"}}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(w.uris, back.uris);
+    assert_eq!(w.statuses, back.statuses);
+    assert_eq!(w.method, back.method);
+    assert_eq!(w.headers, back.headers);
+
+    let back: Wrap = bincode::deserialize(&bincode::serialize(&w).unwrap()).unwrap();
+    assert_eq!(w.uris, back.uris);
+}
+
+#[test]
+fn header_map_dedup_values() {
+    use http::HeaderMap;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_dedup_values")] HeaderMap);
+
+    let back: Wrap =
+        serde_json::from_str("{\"x\":[\"a\",\"a\",\"b\",\"A\"],\"y\":[\"c\",\"c\"]}").unwrap();
+    let x: Vec<_> = back.0.get_all("x").iter().collect();
+    assert_eq!(vec!["a", "b", "A"], x);
+    let y: Vec<_> = back.0.get_all("y").iter().collect();
+    assert_eq!(vec!["c"], y);
+
+    let mut map = HeaderMap::new();
+    map.append("x", "a".parse().unwrap());
+    map.append("x", "a".parse().unwrap());
+    map.append("x", "b".parse().unwrap());
+    let bin = bincode::serialize(&Wrap(map)).unwrap();
+    let back: Wrap = bincode::deserialize(&bin).unwrap();
+    let x: Vec<_> = back.0.get_all("x").iter().collect();
+    assert_eq!(vec!["a", "b"], x);
+}
+
+#[test]
+fn wrap() {
+    use http::{Method, StatusCode, Uri};
+    use http_serde::wrap;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrapped {
+        uris: Vec<wrap::Uri>,
+        methods: BTreeMap<String, wrap::Method>,
+        status: wrap::StatusCode,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Annotated {
+        #[serde(with = "http_serde::status_code")]
+        status: StatusCode,
+    }
+
+    let w = Wrapped {
+        uris: vec![Uri::from_static("/a").into()],
+        methods: vec![("x".to_string(), Method::PUT.into())]
+            .into_iter()
+            .collect(),
+        status: StatusCode::IM_A_TEAPOT.into(),
+    };
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!(
+        "{\"uris\":[\"/a\"],\"methods\":{\"x\":\"PUT\"},\"status\":418}",
+        json
+    );
+    let back: Wrapped = serde_json::from_str(&json).unwrap();
+    assert_eq!("/a", back.uris[0].path());
+    assert_eq!(Method::PUT, *back.methods["x"]);
+    let status: StatusCode = back.status.into();
+    assert_eq!(StatusCode::IM_A_TEAPOT, status);
+
+    let annotated = Annotated {
+        status: StatusCode::IM_A_TEAPOT,
+    };
+    assert_eq!(
+        bincode::serialize(&annotated).unwrap(),
+        bincode::serialize(&w.status).unwrap()
+    );
+    let back: Wrapped = bincode::deserialize(&bincode::serialize(&w).unwrap()).unwrap();
+    assert_eq!(w.uris, back.uris);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn header_map_bincode_from_reader() {
+    use http::HeaderMap;
+    use std::io::Cursor;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.append("x-multi", "a".parse().unwrap());
+    map.append("x-multi", "b".parse().unwrap());
+    map.insert("x-bin", http::HeaderValue::from_bytes(b"\xff\x80").unwrap());
+
+    let bin = bincode::serialize(&Wrap(map.clone())).unwrap();
+    let back: Wrap = bincode::deserialize_from(Cursor::new(bin)).unwrap();
+    assert_eq!(map, back.0);
+}
+
+#[test]
+fn uri_with_ext() {
+    use http::Uri;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_with_ext")] Uri);
+
+    let uri = Uri::from_static("https://x/a/b.json?q=1");
+    let json = serde_json::to_string(&Wrap(uri.clone())).unwrap();
+    assert_eq!(
+        "{\"uri\":\"https://x/a/b.json?q=1\",\"ext\":\"json\"}",
+        json
+    );
+    assert_eq!(uri, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let ext =
+        |s: &'static str| http_serde::uri_with_ext::ext(&Uri::from_static(s)).map(String::from);
+    assert_eq!(Some("gz".to_string()), ext("/a.tar.gz"));
+    assert_eq!(None, ext("/a.dir/"));
+    assert_eq!(None, ext("/a/b?file=c.json"));
+    assert_eq!(None, ext("/.env"));
+    assert_eq!(None, ext("/a."));
+    assert_eq!(None, ext("https://x"));
+
+    let json = serde_json::to_string(&Wrap(Uri::from_static("/dir/"))).unwrap();
+    assert_eq!("{\"uri\":\"/dir/\",\"ext\":null}", json);
+    let back: Wrap = serde_json::from_str("{\"uri\":\"/x.png\"}").unwrap();
+    assert_eq!("/x.png", back.0);
+
+    let bin = bincode::serialize(&Wrap(uri.clone())).unwrap();
+    assert_eq!(uri, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+}
+
+#[test]
+fn status_code_cacheable() {
+    use http::StatusCode;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code_cacheable")] StatusCode);
+
+    assert_eq!(
+        "{\"code\":200,\"cacheable\":true}",
+        serde_json::to_string(&Wrap(StatusCode::OK)).unwrap()
+    );
+    assert_eq!(
+        "{\"code\":500,\"cacheable\":false}",
+        serde_json::to_string(&Wrap(StatusCode::INTERNAL_SERVER_ERROR)).unwrap()
+    );
+
+    let back: Wrap = serde_json::from_str("{\"code\":500,\"cacheable\":true}").unwrap();
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, back.0);
+    let bin = bincode::serialize(&Wrap(StatusCode::GONE)).unwrap();
+    assert_eq!(
+        StatusCode::GONE,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+
+    fn also_500<S: serde::Serializer>(status: &StatusCode, ser: S) -> Result<S::Ok, S::Error> {
+        http_serde::status_code_cacheable::serialize_with(status, &[200, 500], ser)
+    }
+    #[derive(serde::Serialize)]
+    struct Custom(#[serde(serialize_with = "also_500")] StatusCode);
+    assert_eq!(
+        "{\"code\":500,\"cacheable\":true}",
+        serde_json::to_string(&Custom(StatusCode::INTERNAL_SERVER_ERROR)).unwrap()
+    );
+}
+
+#[test]
+fn header_map_ordered() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap {
+        #[serde(with = "http_serde::header_map::ordered")]
+        headers: HeaderMap,
+        #[serde(with = "http_serde::option::header_map_ordered", default)]
+        trailers: Option<HeaderMap>,
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.append("z", HeaderValue::from_static("1"));
+    headers.append("a", HeaderValue::from_static("2"));
+    headers.append("z", HeaderValue::from_static("3"));
+    headers.append("x-bin", HeaderValue::from_bytes(b"\xff").unwrap());
+    let w = Wrap {
+        headers,
+        trailers: None,
+    };
+
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!(
+        "{\"headers\":[[\"z\",\"1\"],[\"z\",\"3\"],[\"a\",\"2\"],[\"x-bin\",[255]]],\"trailers\":null}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(w.headers, back.headers);
+    let order: Vec<_> = back
+        .headers
+        .iter()
+        .map(|(n, v)| (n.as_str(), v.as_bytes()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("z", &b"1"[..]),
+            ("z", b"3"),
+            ("a", b"2"),
+            ("x-bin", b"\xff")
+        ],
+        order
+    );
+
+    let back: Wrap =
+        serde_json::from_str("{\"headers\":[],\"trailers\":[[\"b\",\"1\"],[\"a\",\"2\"]]}")
+            .unwrap();
+    assert!(back.headers.is_empty());
+    let names: Vec<_> = back
+        .trailers
+        .unwrap()
+        .keys()
+        .map(|k| k.to_string())
+        .collect();
+    assert_eq!(vec!["b", "a"], names);
+
+    let back: Wrap = bincode::deserialize(&bincode::serialize(&w).unwrap()).unwrap();
+    assert_eq!(w.headers, back.headers);
+}
+
+#[test]
+fn header_map_base64() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map::base64")] HeaderMap);
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Plain(#[serde(with = "http_serde::header_map")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert("x-one", HeaderValue::from_bytes(b"\xff").unwrap());
+    map.append("x-multi", HeaderValue::from_static("ok"));
+    map.append("x-multi", HeaderValue::from_bytes(b"caf\xe9!").unwrap());
+    map.insert("x-two", HeaderValue::from_bytes(b"\xfe\x80").unwrap());
+    map.insert("x-text", HeaderValue::from_static("hello"));
+
+    let json = serde_json::to_string(&Wrap(map.clone())).unwrap();
+    assert_eq!(
+        "{\"x-one\":{\"base64\":\"/w==\"},\"x-multi\":[\"ok\",{\"base64\":\"Y2Fm6SE=\"}],\"x-two\":{\"base64\":\"/oA=\"},\"x-text\":\"hello\"}",
+        json
+    );
+    assert_eq!(map, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let yaml = serde_yaml::to_string(&Wrap(map.clone())).unwrap();
+    assert_eq!(map, serde_yaml::from_str::<Wrap>(&yaml).unwrap().0);
+    let bin = bincode::serialize(&Wrap(map.clone())).unwrap();
+    assert_eq!(map, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+
+    let back: Wrap = serde_json::from_str("{\"a\":{\"base64\":\"YWJj\"}}").unwrap();
+    assert_eq!("abc", back.0["a"]);
+    assert!(serde_json::from_str::<Wrap>("{\"a\":{\"base64\":\"Y\"}}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"a\":{\"base64\":\"!!\"}}").is_err());
+
+    // The default module doesn't drop values that aren't UTF-8 either
+    let json = serde_json::to_string(&Plain(map.clone())).unwrap();
+    assert_eq!(
+        "{\"x-one\":[[255]],\"x-multi\":[[111,107],[99,97,102,233,33]],\"x-two\":[[254,128]],\"x-text\":\"hello\"}",
+        json
+    );
+    assert_eq!(map, serde_json::from_str::<Plain>(&json).unwrap().0);
+}
+
+#[test]
+fn header_map_signing() {
+    use http::HeaderMap;
+
+    fn signed<S: serde::Serializer>(headers: &HeaderMap, ser: S) -> Result<S::Ok, S::Error> {
+        http_serde::header_map_signing(&["Host", "date"]).serialize(headers, ser)
+    }
+
+    fn read_signed<'de, D: serde::Deserializer<'de>>(de: D) -> Result<HeaderMap, D::Error> {
+        http_serde::header_map_signing(&["Host", "date"]).deserialize(de)
+    }
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(serialize_with = "signed", deserialize_with = "read_signed")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert("date", "Tue, 20 Apr 2021 02:07:55 GMT".parse().unwrap());
+    map.insert("content-type", "text/plain".parse().unwrap());
+    map.insert("host", " example.com ".parse().unwrap());
+
+    let json = serde_json::to_string(&Wrap(map)).unwrap();
+    assert_eq!(
+        "\"host: example.com\\ndate: Tue, 20 Apr 2021 02:07:55 GMT\"",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(2, back.0.len());
+    assert_eq!("example.com", back.0["host"]);
+    assert!(!back.0.contains_key("content-type"));
+
+    let mut multi = HeaderMap::new();
+    multi.append("host", "a".parse().unwrap());
+    multi.append("date", "x".parse().unwrap());
+    multi.append("date", "y".parse().unwrap());
+    assert_eq!(
+        "\"host: a\\ndate: x, y\"",
+        serde_json::to_string(&Wrap(multi)).unwrap()
+    );
+
+    let mut missing = HeaderMap::new();
+    missing.insert("host", "a".parse().unwrap());
+    assert!(serde_json::to_string(&Wrap(missing)).is_err());
+    assert!(serde_json::from_str::<Wrap>("\"host: a\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("\"host: a\\ndate: b\\nx-extra: c\"").is_err());
+}
+
+#[test]
+fn method_webdav() {
+    use http::Method;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::method_webdav")] Method);
+
+    let back: Wrap = serde_json::from_str("\"PROPFIND\"").unwrap();
+    assert_eq!("PROPFIND", back.0);
+    assert_eq!("\"PROPFIND\"", serde_json::to_string(&back).unwrap());
+    let back: Wrap = serde_json::from_str("\"PATCH\"").unwrap();
+    assert_eq!(Method::PATCH, back.0);
+
+    let err = serde_json::from_str::<Wrap>("\"FOOBAR\"").unwrap_err();
+    assert!(
+        err.to_string().contains("standard or WebDAV method"),
+        "{}",
+        err
+    );
+    assert!(serde_json::from_str::<Wrap>("\"propfind\"").is_err());
+
+    let bin = bincode::serialize(&Wrap("MKCOL".parse().unwrap())).unwrap();
+    assert_eq!("MKCOL", bincode::deserialize::<Wrap>(&bin).unwrap().0);
+}
+
+#[test]
+fn status_code_from_string() {
+    use http::StatusCode;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code")] StatusCode);
+
+    let back: Wrap = serde_json::from_str("\"404\"").unwrap();
+    assert_eq!(StatusCode::NOT_FOUND, back.0);
+    assert_eq!("404", serde_json::to_string(&back).unwrap());
+    let back: Wrap = serde_json::from_str("201").unwrap();
+    assert_eq!(StatusCode::CREATED, back.0);
+    let back: Wrap = serde_yaml::from_str("'503'").unwrap();
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, back.0);
+
+    assert!(serde_json::from_str::<Wrap>("\"Not Found\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("\"99\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("\"70000\"").is_err());
+}
+
+#[test]
+fn status_code_with_reason() {
+    use http::StatusCode;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code::with_reason")] StatusCode);
+
+    let json = serde_json::to_string(&Wrap(StatusCode::NOT_FOUND)).unwrap();
+    assert_eq!("{\"code\":404,\"reason\":\"Not Found\"}", json);
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    let custom = StatusCode::from_u16(599).unwrap();
+    let json = serde_json::to_string(&Wrap(custom)).unwrap();
+    assert_eq!("{\"code\":599,\"reason\":null}", json);
+    assert_eq!(custom, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let back: Wrap = serde_json::from_str("204").unwrap();
+    assert_eq!(StatusCode::NO_CONTENT, back.0);
+    let back: Wrap = serde_json::from_str("{\"code\":500,\"reason\":\"Oops\"}").unwrap();
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, back.0);
+    assert!(serde_json::from_str::<Wrap>("{\"reason\":\"Not Found\"}").is_err());
+
+    let bin = bincode::serialize(&Wrap(StatusCode::GONE)).unwrap();
+    assert_eq!(bincode::serialize(&410u16).unwrap(), bin);
+    assert_eq!(
+        StatusCode::GONE,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+}
+
+#[test]
+fn uri_authority_pq() {
+    use http::Uri;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::uri_authority_pq")] Uri);
+
+    let uri = Uri::from_static("https://x/a?b=c");
+    let json = serde_json::to_string(&Wrap(uri.clone())).unwrap();
+    assert_eq!(
+        "{\"scheme\":\"https\",\"authority\":\"x\",\"path_and_query\":\"/a?b=c\"}",
+        json
+    );
+    assert_eq!(uri, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let json = serde_json::to_string(&Wrap(Uri::from_static("/a?b=c"))).unwrap();
+    assert_eq!(
+        "{\"scheme\":null,\"authority\":null,\"path_and_query\":\"/a?b=c\"}",
+        json
+    );
+    assert_eq!("/a?b=c", serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let json = serde_json::to_string(&Wrap(Uri::from_static("x:443"))).unwrap();
+    assert_eq!(
+        "{\"scheme\":null,\"authority\":\"x:443\",\"path_and_query\":null}",
+        json
+    );
+    assert_eq!("x:443", serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let back: Wrap = serde_json::from_str("{\"path_and_query\":\"/p\"}").unwrap();
+    assert_eq!("/p", back.0);
+    assert!(
+        serde_json::from_str::<Wrap>("{\"scheme\":\"https\",\"path_and_query\":\"/p\"}").is_err()
+    );
+
+    let bin = bincode::serialize(&Wrap(uri.clone())).unwrap();
+    assert_eq!(uri, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+}
+
+#[test]
+fn header_map_hex() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_hex")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert("x-bin", HeaderValue::from_bytes(b"\t\x80\xff").unwrap());
+    map.append("x-multi", HeaderValue::from_static("a"));
+    map.append("x-multi", HeaderValue::from_static("b c"));
+
+    let json = serde_json::to_string(&Wrap(map.clone())).unwrap();
+    assert_eq!(
+        "{\"x-bin\":\"0980ff\",\"x-multi\":[\"61\",\"622063\"]}",
+        json
+    );
+    assert!(json.is_ascii());
+    assert_eq!(map, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let bin = bincode::serialize(&Wrap(map.clone())).unwrap();
+    assert_eq!(map, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+
+    let back: Wrap = serde_json::from_str("{\"x\":\"4A4b\"}").unwrap();
+    assert_eq!("JK", back.0["x"]);
+    assert!(serde_json::from_str::<Wrap>("{\"x\":\"abc\"}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"x\":\"zz\"}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"x\":\"0a\"}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"x\":\"+a\"}").is_err());
+    // `+9` would otherwise be a tab, which is a valid header value
+    assert!(serde_json::from_str::<Wrap>("{\"x\":\"61+9\"}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"x\":\"6109\"}").is_ok());
+}
+
+#[test]
+fn method_case_insensitive() {
+    use http::Method;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::method")] Method);
+
+    let back: Wrap = serde_json::from_str("\"get\"").unwrap();
+    assert_eq!(Method::GET, back.0);
+    assert_eq!("\"GET\"", serde_json::to_string(&back).unwrap());
+    let back: Wrap = serde_json::from_str("\"Post\"").unwrap();
+    assert_eq!(Method::POST, back.0);
+
+    let back: Wrap = serde_json::from_str("\"PATCH-EXT\"").unwrap();
+    assert_eq!("PATCH-EXT", back.0);
+    let back: Wrap = serde_json::from_str("\"propFind\"").unwrap();
+    assert_eq!("propFind", back.0);
+    assert_eq!("\"propFind\"", serde_json::to_string(&back).unwrap());
+
+    assert!(serde_json::from_str::<Wrap>("\"GET X\"").is_err());
+    assert!(serde_json::from_str::<Wrap>("\"\"").is_err());
+}
+
+#[test]
+fn version_binary() {
+    use http::Version;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap {
+        #[serde(with = "http_serde::version")]
+        version: Version,
+        #[serde(with = "http_serde::option::version")]
+        opt: Option<Version>,
+    }
+
+    let w = Wrap {
+        version: Version::HTTP_2,
+        opt: Some(Version::HTTP_10),
+    };
+    let bin = bincode::serialize(&w).unwrap();
+    assert_eq!(vec![3, 1, 1], bin);
+    let back: Wrap = bincode::deserialize(&bin).unwrap();
+    assert_eq!(Version::HTTP_2, back.version);
+    assert_eq!(Some(Version::HTTP_10), back.opt);
+
+    let bin = bincode::serialize(&Wrap {
+        version: Version::HTTP_3,
+        opt: None,
+    })
+    .unwrap();
+    assert_eq!(vec![4, 0], bin);
+    assert_eq!(None, bincode::deserialize::<Wrap>(&bin).unwrap().opt);
+    assert!(bincode::deserialize::<Wrap>(&[5, 0]).is_err());
+    assert!(bincode::deserialize::<Wrap>(&[255, 0]).is_err());
+
+    let cbor = serde_cbor::to_vec(&w).unwrap();
+    let back: Wrap = serde_cbor::from_slice(&cbor).unwrap();
+    assert_eq!(Version::HTTP_2, back.version);
+
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!("{\"version\":\"HTTP/2.0\",\"opt\":\"HTTP/1.0\"}", json);
+    assert_eq!(
+        Some(Version::HTTP_10),
+        serde_json::from_str::<Wrap>(&json).unwrap().opt
+    );
+}
+
+#[test]
+fn status_code_result() {
+    use http::StatusCode;
+    use http_serde::status_code_result::{from_result, to_result};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::status_code_result")] StatusCode);
+
+    let json = serde_json::to_string(&Wrap(StatusCode::OK)).unwrap();
+    assert_eq!("{\"ok\":200}", json);
+    assert_eq!(
+        StatusCode::OK,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    let json = serde_json::to_string(&Wrap(StatusCode::BAD_GATEWAY)).unwrap();
+    assert_eq!("{\"err\":502}", json);
+    assert_eq!(
+        StatusCode::BAD_GATEWAY,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    assert!(serde_json::from_str::<Wrap>("{\"ok\":500}").is_err());
+    assert!(serde_json::from_str::<Wrap>("{\"err\":204}").is_err());
+
+    assert_eq!(Ok(StatusCode::CREATED), to_result(StatusCode::CREATED));
+    assert_eq!(Err(StatusCode::NOT_FOUND), to_result(StatusCode::NOT_FOUND));
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        from_result(Err(StatusCode::NOT_FOUND))
+    );
+
+    let bin = bincode::serialize(&Wrap(StatusCode::SERVICE_UNAVAILABLE)).unwrap();
+    assert_eq!(
+        StatusCode::SERVICE_UNAVAILABLE,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+}
+
+#[test]
+fn uri_max_segments() {
+    use http::Uri;
+
+    fn at_most_5<'de, D: serde::Deserializer<'de>>(de: D) -> Result<Uri, D::Error> {
+        http_serde::uri_max_segments(5).deserialize(de)
+    }
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(
+        #[serde(
+            serialize_with = "http_serde::uri::serialize",
+            deserialize_with = "at_most_5"
+        )]
+        Uri,
+    );
+
+    let back: Wrap = serde_json::from_str("\"https://x/a/b/c?d=/e/f/g\"").unwrap();
+    assert_eq!("/a/b/c", back.0.path());
+    let back: Wrap = serde_json::from_str("\"/1/2/3/4/5/\"").unwrap();
+    assert_eq!("/1/2/3/4/5/", back.0);
+
+    let err = serde_json::from_str::<Wrap>("\"/1/2/3/4/5/6/7/8/9/10\"").unwrap_err();
+    assert!(err.to_string().contains("10 segments"), "{}", err);
+    assert!(serde_json::from_str::<Wrap>("\"/1/2/3/4/5/6\"").is_err());
+}
+
+#[test]
+fn header_map_sorted() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map::sorted")] HeaderMap);
+
+    let mut a = HeaderMap::new();
+    a.insert("zeta", HeaderValue::from_static("1"));
+    a.append("alpha", HeaderValue::from_static("x"));
+    a.append("alpha", HeaderValue::from_static("y"));
+    a.insert("mid", HeaderValue::from_static("2"));
+
+    let mut b = HeaderMap::new();
+    b.insert("mid", HeaderValue::from_static("2"));
+    b.append("alpha", HeaderValue::from_static("x"));
+    b.insert("zeta", HeaderValue::from_static("1"));
+    b.append("alpha", HeaderValue::from_static("y"));
+
+    let json_a = serde_json::to_string(&Wrap(a.clone())).unwrap();
+    let json_b = serde_json::to_string(&Wrap(b)).unwrap();
+    assert_eq!(json_a, json_b);
+    assert_eq!(
+        "{\"alpha\":[\"x\",\"y\"],\"mid\":\"2\",\"zeta\":\"1\"}",
+        json_a
+    );
+    assert_eq!(a, serde_json::from_str::<Wrap>(&json_a).unwrap().0);
+
+    let bin = bincode::serialize(&Wrap(a.clone())).unwrap();
+    assert_eq!(a, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+}
+
+#[cfg(all(feature = "mime", feature = "std"))]
+#[test]
+fn header_map_mime() {
+    use http::{HeaderMap, HeaderValue};
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::header_map_mime")] HeaderMap);
+
+    let mut map = HeaderMap::new();
+    map.insert(
+        "content-type",
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    map.append("x-multi", HeaderValue::from_static("a"));
+    map.append("x-multi", HeaderValue::from_static("b"));
+
+    let json = serde_json::to_string(&Wrap(map.clone())).unwrap();
+    assert_eq!(
+        "{\"content-type\":{\"type\":\"application\",\"subtype\":\"json\",\"params\":{\"charset\":\"utf-8\"}},\"x-multi\":[\"a\",\"b\"]}",
+        json
+    );
+    let back: Wrap = serde_json::from_str(&json).unwrap();
+    assert_eq!(map, back.0);
+
+    let back: Wrap = serde_json::from_str(
+        "{\"Content-Type\":{\"type\":\"text\",\"subtype\":\"plain\",\"params\":{\"format\":\"a b\"}}}",
+    )
+    .unwrap();
+    assert_eq!("text/plain; format=\"a b\"", back.0["content-type"]);
+
+    let mut no_type = HeaderMap::new();
+    no_type.insert("x-a", HeaderValue::from_static("1"));
+    let json = serde_json::to_string(&Wrap(no_type.clone())).unwrap();
+    assert_eq!("{\"x-a\":\"1\"}", json);
+    assert_eq!(no_type, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    let mut invalid = HeaderMap::new();
+    invalid.insert("content-type", HeaderValue::from_static("not a mime"));
+    let json = serde_json::to_string(&Wrap(invalid.clone())).unwrap();
+    assert_eq!("{\"content-type\":\"not a mime\"}", json);
+    assert_eq!(invalid, serde_json::from_str::<Wrap>(&json).unwrap().0);
+
+    assert!(serde_json::from_str::<Wrap>(
+        "{\"content-type\":{\"type\":\"a b\",\"subtype\":\"c\"}}"
+    )
+    .is_err());
+
+    let bin = bincode::serialize(&Wrap(map.clone())).unwrap();
+    assert_eq!(map, bincode::deserialize::<Wrap>(&bin).unwrap().0);
+}
+
+#[test]
+fn version_transport() {
+    use http::Version;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Wrap(#[serde(with = "http_serde::version_transport")] Version);
+
+    let json = serde_json::to_string(&Wrap(Version::HTTP_3)).unwrap();
+    assert_eq!("\"h3 (QUIC)\"", json);
+    assert_eq!(
+        Version::HTTP_3,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    let json = serde_json::to_string(&Wrap(Version::HTTP_2)).unwrap();
+    assert_eq!("\"HTTP/2.0\"", json);
+    assert_eq!(
+        Version::HTTP_2,
+        serde_json::from_str::<Wrap>(&json).unwrap().0
+    );
+
+    assert_eq!(
+        Version::HTTP_3,
+        serde_json::from_str::<Wrap>("\"h3\"").unwrap().0
+    );
+    assert_eq!(
+        Version::HTTP_3,
+        serde_json::from_str::<Wrap>("\"HTTP/3.0\"").unwrap().0
+    );
+    assert!(serde_json::from_str::<Wrap>("\"h2 (QUIC)\"").is_err());
+
+    let bin = bincode::serialize(&Wrap(Version::HTTP_3)).unwrap();
+    assert_eq!(vec![4], bin);
+    assert_eq!(
+        Version::HTTP_3,
+        bincode::deserialize::<Wrap>(&bin).unwrap().0
+    );
+}